@@ -0,0 +1,469 @@
+use std::{
+    convert::TryFrom,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+pub const USAGE: &str = "Usage: type-lang <command> [options] [schema]
+
+Commands:
+  generate    Generate code for all types of the schema and its imports
+  watch       Generate code whenever the schema or one of its imports changes
+  check       Check the schema and its imports for errors without generating code
+  fmt         Print the schema in canonical formatting, or rewrite the schemas with --write
+  lsp         Run a language server for editors on stdin and stdout
+
+Without a schema, generate, watch, check, fmt --check and fmt --write use the inputs of the type.toml in the current directory,
+whose options are also used for all languages that are not overridden on the command line.
+A schema of - is read from stdin, in which case the generated files are printed to stdout.
+
+Options:
+  --lang <languages>  Comma separated languages to generate: gleam, rust, mermaid, graphviz
+                      (default: gleam,rust,mermaid)
+  --out <directory>   Directory that each language is generated into a subdirectory of,
+                      or - to print all files to stdout (default: .)
+  --config <file>     Configuration file to use instead of ./type.toml
+  --check             Only compare the generated files to the ones on disk and print their differences,
+                      or with fmt, print the differences of schemas that are not formatted
+  --write             Rewrite the schemas in canonical formatting instead of printing them, only with fmt
+  -h, --help          Print this help
+
+Exit codes:
+  0  Success
+  1  The schema has errors, the output could not be written or, with --check, is out of date or not formatted
+  2  Invalid command line arguments";
+
+/// The path that stands for stdin as schema and for stdout as output directory
+pub const STDIO: &str = "-";
+
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Generate {
+        languages: Option<Vec<Language>>,
+        out: Option<PathBuf>,
+        input: Option<PathBuf>,
+        config: Option<PathBuf>,
+        /// Compare the generated files to the ones on disk instead of writing them
+        check: bool,
+    },
+    Watch {
+        languages: Option<Vec<Language>>,
+        out: Option<PathBuf>,
+        input: Option<PathBuf>,
+        config: Option<PathBuf>,
+    },
+    Check {
+        input: Option<PathBuf>,
+        config: Option<PathBuf>,
+    },
+    Fmt {
+        input: Option<PathBuf>,
+        config: Option<PathBuf>,
+        check: bool,
+        /// Rewrite the schemas in place instead of printing the formatting
+        write: bool,
+    },
+    Lsp,
+    Help,
+}
+
+/// A target language, each with its own generator and output directory
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum Language {
+    Gleam,
+    Rust,
+    Mermaid,
+    Graphviz,
+}
+
+impl Language {
+    pub const DEFAULT: [Language; 3] = [Language::Gleam, Language::Rust, Language::Mermaid];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Gleam => "gleam",
+            Language::Rust => "rust",
+            Language::Mermaid => "mermaid",
+            Language::Graphviz => "graphviz",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gleam" => Ok(Language::Gleam),
+            "rust" | "rs" => Ok(Language::Rust),
+            "mermaid" | "mmd" => Ok(Language::Mermaid),
+            "graphviz" | "dot" => Ok(Language::Graphviz),
+            _ => Err(format!("Unsupported language '{s}'")),
+        }
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Parses the command line arguments without the name of the executable
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Err("Missing command".to_owned()),
+    };
+    if matches!(command.as_str(), "-h" | "--help" | "help") {
+        return Ok(Command::Help);
+    }
+
+    let mut languages = None;
+    let mut out = None;
+    let mut config = None;
+    let mut check = false;
+    let mut write = false;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        // Options can either be followed by their value or be written as `--option=value`
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if arg.starts_with("--") => (option.to_owned(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| match inline_value {
+            Some(value) => Ok(value.to_owned()),
+            None => args.next().ok_or(format!("Missing value for {name}")),
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--lang" => {
+                let value = value("--lang")?;
+                let parsed = value
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<Language>, _>>()?;
+                languages = Some(parsed);
+            }
+            "--out" => out = Some(PathBuf::from(value("--out")?)),
+            "--config" => config = Some(PathBuf::from(value("--config")?)),
+            "--check" if inline_value.is_none() => check = true,
+            "--write" if inline_value.is_none() => write = true,
+            _ if option.starts_with('-') && option != STDIO => {
+                return Err(format!("Unknown option '{option}'"))
+            }
+            _ if input.is_some() => return Err(format!("Unexpected argument '{arg}'")),
+            _ => input = Some(PathBuf::from(arg)),
+        }
+    }
+
+    // The extension of the schema can be left out
+    let input = input.map(|mut input: PathBuf| {
+        if input.extension().is_none() && !is_stdio(&input) {
+            input.set_extension("type");
+        }
+        input
+    });
+
+    let only_for = |option: &str, commands: &str, used: bool| match used {
+        true => Err(format!("{option} can only be used with {commands}")),
+        false => Ok(()),
+    };
+
+    if command != "fmt" {
+        only_for("--write", "fmt", write)?;
+    }
+
+    match command.as_str() {
+        "generate" => {
+            // Files read from stdin are printed to stdout, unless an output directory is given
+            let from_stdin = input.as_deref().is_some_and(is_stdio);
+            let out = out.or_else(|| from_stdin.then(|| PathBuf::from(STDIO)));
+            if check && out.as_deref().is_some_and(is_stdio) {
+                return Err("--check cannot be used when printing to stdout".to_owned());
+            }
+
+            Ok(Command::Generate {
+                languages,
+                out,
+                input,
+                config,
+                check,
+            })
+        }
+        "watch" => {
+            only_for("--check", "generate and fmt", check)?;
+            if input.as_deref().is_some_and(is_stdio) || out.as_deref().is_some_and(is_stdio) {
+                return Err("watch cannot read from stdin or print to stdout".to_owned());
+            }
+            Ok(Command::Watch {
+                languages,
+                out,
+                input,
+                config,
+            })
+        }
+        "check" => {
+            only_for("--lang", "generate and watch", languages.is_some())?;
+            only_for("--out", "generate and watch", out.is_some())?;
+            only_for("--check", "generate and fmt", check)?;
+            Ok(Command::Check { input, config })
+        }
+        "fmt" => {
+            only_for("--lang", "generate and watch", languages.is_some())?;
+            only_for("--out", "generate and watch", out.is_some())?;
+            if check && write {
+                return Err("--check and --write cannot be used together".to_owned());
+            }
+            if write && input.as_deref().is_some_and(is_stdio) {
+                return Err("--write cannot be used with a schema from stdin".to_owned());
+            }
+            // Printing works on a single schema, while checking and writing also work on all schemas of the configuration
+            if !check && !write && input.is_none() {
+                return Err("Missing schema file".to_owned());
+            }
+            Ok(Command::Fmt {
+                input,
+                config,
+                check,
+                write,
+            })
+        }
+        "lsp" => {
+            only_for("--lang", "generate and watch", languages.is_some())?;
+            only_for("--out", "generate and watch", out.is_some())?;
+            only_for("--check", "generate and fmt", check)?;
+            only_for(
+                "--config",
+                "generate, watch, check and fmt",
+                config.is_some(),
+            )?;
+            match input {
+                Some(input) => Err(format!("Unexpected argument '{}'", input.display())),
+                None => Ok(Command::Lsp),
+            }
+        }
+        _ => Err(format!("Unknown command '{command}'")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{parse_args, Command, Language};
+
+    fn parse(args: &str) -> Result<Command, String> {
+        parse_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn parse_generate() {
+        assert_eq!(
+            parse("generate --lang rust,dot --out gen/ schema.type"),
+            Ok(Command::Generate {
+                languages: Some(vec![Language::Rust, Language::Graphviz]),
+                out: Some(PathBuf::from("gen/")),
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+                check: false,
+            })
+        );
+        assert_eq!(
+            parse("generate schema --out=gen"),
+            Ok(Command::Generate {
+                languages: None,
+                out: Some(PathBuf::from("gen")),
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+                check: false,
+            })
+        );
+        assert_eq!(
+            parse("generate --config project/type.toml"),
+            Ok(Command::Generate {
+                languages: None,
+                out: None,
+                input: None,
+                config: Some(PathBuf::from("project/type.toml")),
+                check: false,
+            })
+        );
+        assert_eq!(
+            parse("generate --check schema"),
+            Ok(Command::Generate {
+                languages: None,
+                out: None,
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+                check: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_check_and_fmt() {
+        assert_eq!(
+            parse("check schema.type"),
+            Ok(Command::Check {
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+            })
+        );
+        assert_eq!(
+            parse("fmt schema.type"),
+            Ok(Command::Fmt {
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+                check: false,
+                write: false,
+            })
+        );
+        assert_eq!(
+            parse("fmt --check --config project/type.toml"),
+            Ok(Command::Fmt {
+                input: None,
+                config: Some(PathBuf::from("project/type.toml")),
+                check: true,
+                write: false,
+            })
+        );
+        assert_eq!(
+            parse("fmt --write"),
+            Ok(Command::Fmt {
+                input: None,
+                config: None,
+                check: false,
+                write: true,
+            })
+        );
+        assert_eq!(parse("lsp"), Ok(Command::Lsp));
+    }
+
+    #[test]
+    fn parse_stdio() {
+        assert_eq!(
+            parse("generate --lang rust -"),
+            Ok(Command::Generate {
+                languages: Some(vec![Language::Rust]),
+                out: Some(PathBuf::from("-")),
+                input: Some(PathBuf::from("-")),
+                config: None,
+                check: false,
+            })
+        );
+        assert_eq!(
+            parse("generate --out gen -"),
+            Ok(Command::Generate {
+                languages: None,
+                out: Some(PathBuf::from("gen")),
+                input: Some(PathBuf::from("-")),
+                config: None,
+                check: false,
+            })
+        );
+        assert_eq!(
+            parse("fmt -"),
+            Ok(Command::Fmt {
+                input: Some(PathBuf::from("-")),
+                config: None,
+                check: false,
+                write: false,
+            })
+        );
+        assert_eq!(
+            parse("fmt --write -"),
+            Err("--write cannot be used with a schema from stdin".to_owned())
+        );
+
+        assert_eq!(
+            parse("generate --check -"),
+            Err("--check cannot be used when printing to stdout".to_owned())
+        );
+        assert_eq!(
+            parse("watch -"),
+            Err("watch cannot read from stdin or print to stdout".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_watch() {
+        assert_eq!(
+            parse("watch --lang gleam schema.type"),
+            Ok(Command::Watch {
+                languages: Some(vec![Language::Gleam]),
+                out: None,
+                input: Some(PathBuf::from("schema.type")),
+                config: None,
+            })
+        );
+        assert_eq!(
+            parse("watch --check"),
+            Err("--check can only be used with generate and fmt".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_help() {
+        assert_eq!(parse("--help"), Ok(Command::Help));
+        assert_eq!(parse("generate -h"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn reject_invalid_arguments() {
+        assert_eq!(parse(""), Err("Missing command".to_owned()));
+        assert_eq!(
+            parse("build schema.type"),
+            Err("Unknown command 'build'".to_owned())
+        );
+        assert_eq!(parse("fmt"), Err("Missing schema file".to_owned()));
+        assert_eq!(
+            parse("generate --lang ts schema.type"),
+            Err("Unsupported language 'ts'".to_owned())
+        );
+        assert_eq!(
+            parse("generate schema.type --lang"),
+            Err("Missing value for --lang".to_owned())
+        );
+        assert_eq!(
+            parse("check --out gen schema.type"),
+            Err("--out can only be used with generate and watch".to_owned())
+        );
+        assert_eq!(
+            parse("lsp --config type.toml"),
+            Err("--config can only be used with generate, watch, check and fmt".to_owned())
+        );
+        assert_eq!(
+            parse("generate --write schema.type"),
+            Err("--write can only be used with fmt".to_owned())
+        );
+        assert_eq!(
+            parse("fmt --check --write"),
+            Err("--check and --write cannot be used together".to_owned())
+        );
+        assert_eq!(
+            parse("fmt a.type b.type"),
+            Err("Unexpected argument 'b.type'".to_owned())
+        );
+        assert_eq!(
+            parse("lsp schema.type"),
+            Err("Unexpected argument 'schema.type'".to_owned())
+        );
+        assert_eq!(
+            parse("generate --verbose schema.type"),
+            Err("Unknown option '--verbose'".to_owned())
+        );
+    }
+}
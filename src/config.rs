@@ -0,0 +1,217 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use type_lib::{
+    generator::{
+        diagram::{DiagramFormat, DiagramGenerator},
+        gleam::{DecodeVersion, GleamTypeGenerator},
+        rust::RustTypeGenerator,
+        Generator,
+    },
+    inputs,
+};
+
+use cli::Language;
+
+/// The file that is read by default to configure the code generation of a project
+pub const CONFIG_FILE: &str = "type.toml";
+
+/// The project configuration, read from a `type.toml` like
+///
+/// ```toml
+/// inputs = ["schemas/*.type"]
+/// languages = ["gleam", "rust"]
+/// out = "gen"
+///
+/// [gleam]
+/// out = "src/shared"
+/// module_name = "shared"
+/// decode = "stdlib"
+///
+/// [rust]
+/// derives = ["Clone", "Debug", "PartialEq", "Eq", "Hash"]
+/// ```
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Glob patterns of the schemas to generate, imports of these schemas are generated as well
+    pub inputs: Vec<String>,
+    pub languages: Option<Vec<Language>>,
+    /// The directory that each language without its own output directory is generated into a subdirectory of
+    pub out: Option<PathBuf>,
+    pub gleam: GleamConfig,
+    pub rust: RustConfig,
+    pub mermaid: OutputConfig,
+    pub graphviz: OutputConfig,
+
+    /// The directory of the configuration file, which all paths are relative to
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GleamConfig {
+    pub out: Option<PathBuf>,
+    /// The module folder of generated types without a package
+    pub module_name: Option<String>,
+    pub decode: Option<Decode>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Decode {
+    Legacy,
+    Stdlib,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RustConfig {
+    pub out: Option<PathBuf>,
+    /// Traits to derive for generated structs next to the serde traits
+    pub derives: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub out: Option<PathBuf>,
+}
+
+impl Config {
+    /// Reads the configuration file at the given path
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path)
+            .map_err(|error| format!("Could not read '{}': {error}", path.display()))?;
+        let mut config =
+            Self::parse(&source).map_err(|error| format!("{}: {error}", path.display()))?;
+        config.root = path.parent().map(Path::to_owned).unwrap_or_default();
+
+        Ok(config)
+    }
+
+    /// Reads the configuration file of the current directory, if there is one
+    pub fn discover() -> Result<Option<Self>, String> {
+        let path = Path::new(CONFIG_FILE);
+        match path.is_file() {
+            true => Self::read(path).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    pub fn languages(&self) -> Vec<Language> {
+        self.languages
+            .clone()
+            .unwrap_or_else(|| Language::DEFAULT.to_vec())
+    }
+
+    /// The schemas matching the input patterns, in the order of the patterns
+    pub fn inputs(&self) -> Result<Vec<PathBuf>, String> {
+        inputs::resolve(&self.root, &self.inputs).map_err(|error| error.to_string())
+    }
+
+    /// The directory that a language is generated into, which is a subdirectory of `out` unless configured otherwise
+    pub fn output_dir(&self, language: Language) -> PathBuf {
+        let configured = match language {
+            Language::Gleam => &self.gleam.out,
+            Language::Rust => &self.rust.out,
+            Language::Mermaid => &self.mermaid.out,
+            Language::Graphviz => &self.graphviz.out,
+        };
+
+        match configured {
+            Some(out) => self.root.join(out),
+            None => {
+                let out = self.out.as_deref().unwrap_or_else(|| Path::new(""));
+                self.root.join(out).join(language.name())
+            }
+        }
+    }
+
+    pub fn generator(&self, language: Language) -> Box<dyn Generator> {
+        match language {
+            Language::Gleam => {
+                let mut generator = match &self.gleam.module_name {
+                    Some(module_name) => GleamTypeGenerator::new(module_name.clone()),
+                    None => GleamTypeGenerator::default(),
+                };
+                if let Some(decode) = self.gleam.decode {
+                    generator = generator.with_decode_version(match decode {
+                        Decode::Legacy => DecodeVersion::Legacy,
+                        Decode::Stdlib => DecodeVersion::Stdlib,
+                    });
+                }
+                Box::new(generator)
+            }
+            Language::Rust => match &self.rust.derives {
+                Some(derives) => Box::new(RustTypeGenerator::new().with_derives(derives.clone())),
+                None => RustTypeGenerator::boxed(),
+            },
+            Language::Mermaid => Box::new(DiagramGenerator::new(DiagramFormat::Mermaid)),
+            Language::Graphviz => Box::new(DiagramGenerator::new(DiagramFormat::Graphviz)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use cli::Language;
+
+    use super::{Config, Decode};
+
+    #[test]
+    fn parse_config() {
+        let source = r#"
+            inputs = ["schemas/*.type"]
+            languages = ["gleam", "rs"]
+            out = "gen"
+
+            [gleam]
+            module_name = "shared"
+            decode = "stdlib"
+
+            [rust]
+            out = "src/generated"
+            derives = ["Clone", "Hash"]
+        "#;
+        let mut config = Config::parse(source).unwrap();
+        config.root = PathBuf::from("project");
+
+        assert_eq!(config.inputs, vec!["schemas/*.type"]);
+        assert_eq!(config.languages(), vec![Language::Gleam, Language::Rust]);
+        assert_eq!(config.gleam.module_name.as_deref(), Some("shared"));
+        assert_eq!(config.gleam.decode, Some(Decode::Stdlib));
+        assert_eq!(
+            config.output_dir(Language::Gleam),
+            Path::new("project/gen/gleam")
+        );
+        assert_eq!(
+            config.output_dir(Language::Rust),
+            Path::new("project/src/generated")
+        );
+    }
+
+    #[test]
+    fn default_config() {
+        let config = Config::parse("").unwrap();
+
+        assert_eq!(config.languages(), Language::DEFAULT.to_vec());
+        assert_eq!(config.output_dir(Language::Mermaid), Path::new("mermaid"));
+    }
+
+    #[test]
+    fn reject_invalid_config() {
+        assert!(Config::parse(r#"languages = ["ts"]"#).is_err());
+        assert!(Config::parse("[rust]\nderive = []").is_err());
+    }
+}
@@ -0,0 +1,294 @@
+/// Lines of unchanged context that are shown around each change
+const CONTEXT: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit<'a> {
+    Keep(&'a str),
+    Remove(&'a str),
+    Insert(&'a str),
+}
+
+/// Writes a unified diff between two texts, which is empty if they are equal
+pub fn unified_diff(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    let edits = edits(
+        &old.lines().collect::<Vec<_>>(),
+        &new.lines().collect::<Vec<_>>(),
+    );
+    if edits.iter().all(|edit| matches!(edit, Edit::Keep(_))) {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    for hunk in hunks(&edits) {
+        let (old_start, new_start) = hunk.start;
+        let old_len = count(hunk.edits, |edit| !matches!(edit, Edit::Insert(_)));
+        let new_len = count(hunk.edits, |edit| !matches!(edit, Edit::Remove(_)));
+        diff.push_str(&format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            old_start + 1,
+            new_start + 1
+        ));
+
+        for edit in hunk.edits {
+            let line = match edit {
+                Edit::Keep(line) => format!(" {line}\n"),
+                Edit::Remove(line) => format!("-{line}\n"),
+                Edit::Insert(line) => format!("+{line}\n"),
+            };
+            diff.push_str(&line);
+        }
+    }
+
+    diff
+}
+
+struct Hunk<'e, 'a> {
+    /// The zero based line of the hunk in the old and the new text
+    start: (usize, usize),
+    edits: &'e [Edit<'a>],
+}
+
+fn count(edits: &[Edit], predicate: impl Fn(&Edit) -> bool) -> usize {
+    edits.iter().filter(|edit| predicate(edit)).count()
+}
+
+/// Groups changes with their surrounding context, merging changes whose context overlaps
+fn hunks<'e, 'a>(edits: &'e [Edit<'a>]) -> Vec<Hunk<'e, 'a>> {
+    let changes = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Keep(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let before = &edits[..start];
+            Hunk {
+                start: (
+                    count(before, |edit| !matches!(edit, Edit::Insert(_))),
+                    count(before, |edit| !matches!(edit, Edit::Remove(_))),
+                ),
+                edits: &edits[start..end],
+            }
+        })
+        .collect()
+}
+
+/// Computes the shortest edits from the old to the new lines with the linear space variant of Myers' algorithm,
+/// so that large generated files do not need a table of all pairs of lines
+fn edits<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    diff(old, new, &mut edits);
+
+    // Removals are written before insertions within each change, as in other diff tools
+    for change in edits.split_mut(|edit| matches!(edit, Edit::Keep(_))) {
+        change.sort_by_key(|edit| matches!(edit, Edit::Insert(_)));
+    }
+
+    edits
+}
+
+fn diff<'a>(old: &[&'a str], new: &[&'a str], edits: &mut Vec<Edit<'a>>) {
+    let prefix = common_prefix(old, new);
+    edits.extend(old[..prefix].iter().map(|line| Edit::Keep(line)));
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+
+    let suffix = common_suffix(old, new);
+    let (old, new, kept) = (
+        &old[..old.len() - suffix],
+        &new[..new.len() - suffix],
+        &old[old.len() - suffix..],
+    );
+
+    if old.is_empty() {
+        edits.extend(new.iter().map(|line| Edit::Insert(line)));
+    } else if new.is_empty() {
+        edits.extend(old.iter().map(|line| Edit::Remove(line)));
+    } else {
+        let (start, end) = middle_snake(old, new);
+        diff(&old[..start.0], &new[..start.1], edits);
+        edits.extend(old[start.0..end.0].iter().map(|line| Edit::Keep(line)));
+        diff(&old[end.0..], &new[end.1..], edits);
+    }
+
+    edits.extend(kept.iter().map(|line| Edit::Keep(line)));
+}
+
+fn common_prefix(old: &[&str], new: &[&str]) -> usize {
+    old.iter().zip(new).take_while(|(a, b)| a == b).count()
+}
+
+fn common_suffix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Finds the start and end of the common lines in the middle of a shortest edit script,
+/// by searching from both ends until the paths overlap
+fn middle_snake(old: &[&str], new: &[&str]) -> ((usize, usize), (usize, usize)) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    // The furthest x on each diagonal k = x - y, from the start and from the end, offset to be positive
+    let offset = max + 1;
+    let mut forward = vec![0; 2 * offset as usize + 1];
+    let mut backward = vec![0; 2 * offset as usize + 1];
+    let index = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            // Continue from the neighbouring diagonal that reached further, with an insertion or a removal
+            let insert = k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]);
+            let mut x = match insert {
+                true => forward[index(k + 1)],
+                false => forward[index(k - 1)] + 1,
+            };
+            let mut y = x - k;
+            let start = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index(k)] = x;
+
+            // The diagonal of the backward search that this diagonal corresponds to
+            let reverse = delta - k;
+            let overlaps =
+                (-(d - 1)..=d - 1).contains(&reverse) && x + backward[index(reverse)] >= n;
+            if odd && overlaps {
+                return (
+                    (start.0 as usize, start.1 as usize),
+                    (x as usize, y as usize),
+                );
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let insert = k == -d || (k != d && backward[index(k - 1)] < backward[index(k + 1)]);
+            let mut x = match insert {
+                true => backward[index(k + 1)],
+                false => backward[index(k - 1)] + 1,
+            };
+            let mut y = x - k;
+            let end = (n - x, m - y);
+            // The backward search walks the diagonals from the end of both texts
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[index(k)] = x;
+
+            let reverse = delta - k;
+            let overlaps = (-d..=d).contains(&reverse) && x + forward[index(reverse)] >= n;
+            if !odd && overlaps {
+                return (
+                    ((n - x) as usize, (m - y) as usize),
+                    (end.0 as usize, end.1 as usize),
+                );
+            }
+        }
+    }
+
+    unreachable!("The paths from both ends always overlap")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{edits, unified_diff, Edit};
+
+    #[test]
+    fn diff_equal_texts() {
+        assert_eq!(unified_diff("a", "b", "one\ntwo\n", "one\ntwo\n"), "");
+    }
+
+    #[test]
+    fn diff_changed_lines() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\n3\nfour\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+
+        assert_eq!(
+            unified_diff("user.rs", "user.rs (generated)", old, new),
+            "--- user.rs\n+++ user.rs (generated)\n@@ -1,7 +1,7 @@\n 1\n 2\n 3\n-4\n+four\n 5\n 6\n 7\n@@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+    }
+
+    #[test]
+    fn diff_missing_file() {
+        assert_eq!(
+            unified_diff("a", "b", "", "line\n"),
+            "--- a\n+++ b\n@@ -1,0 +1,1 @@\n+line\n"
+        );
+    }
+
+    /// The length of the longest common subsequence, which is the number of lines kept by a shortest diff
+    fn common_lines(old: &[&str], new: &[&str]) -> usize {
+        let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i][j] = match old[i] == new[j] {
+                    true => lengths[i + 1][j + 1] + 1,
+                    false => lengths[i + 1][j].max(lengths[i][j + 1]),
+                };
+            }
+        }
+        lengths[0][0]
+    }
+
+    #[test]
+    fn edits_are_shortest() {
+        let lines = ["a", "b", "c"];
+        // Texts of up to six lines, enumerated from the digits of numbers in base three
+        let texts = (0..3usize.pow(6))
+            .step_by(7)
+            .map(|number| {
+                let length = number % 7;
+                (0..length)
+                    .map(|digit| lines[number / 3usize.pow(digit as u32) % 3])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for old in &texts {
+            for new in &texts {
+                let edits = edits(old, new);
+                let kept = edits
+                    .iter()
+                    .filter(|edit| matches!(edit, Edit::Keep(_)))
+                    .count();
+                let before = edits
+                    .iter()
+                    .filter_map(|edit| match edit {
+                        Edit::Keep(line) | Edit::Remove(line) => Some(*line),
+                        Edit::Insert(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                let after = edits
+                    .iter()
+                    .filter_map(|edit| match edit {
+                        Edit::Keep(line) | Edit::Insert(line) => Some(*line),
+                        Edit::Remove(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                assert_eq!((&before, &after), (old, new));
+                assert_eq!(kept, common_lines(old, new), "{old:?} -> {new:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Includes the types of `.type` schemas in Rust crates, see [`include_types!`].
+
+extern crate type_lang_macros;
+
+pub use type_lang_macros::include_types;
@@ -0,0 +1,801 @@
+//! A language server for schemas, which talks to editors over stdin and stdout
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    io::{self, BufRead, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use serde_json::{json, Value};
+use type_lib::{
+    checker, formatter,
+    loader::{LoadError, Loader, SymbolTable},
+    outline::{Outline, SymbolKind},
+    parser::{qualify, split_qualified, Declaration, BUILTIN_TYPES},
+};
+
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const PARSE_ERROR: i64 = -32700;
+
+/// Documents are always sent in full instead of as incremental changes
+const FULL_SYNC: u8 = 1;
+
+const COMPLETION_KEYWORD: u8 = 14;
+const COMPLETION_STRUCT: u8 = 22;
+const COMPLETION_TYPE_PARAMETER: u8 = 25;
+
+/// Serves requests until the client asks the server to exit
+pub fn serve() -> Result<(), String> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut input = stdin.lock();
+    let mut output = stdout.lock();
+    let mut server = Server::new();
+
+    loop {
+        let content = read_message(&mut input)
+            .map_err(|error| format!("Could not read message: {error}"))?
+            .ok_or("The client closed the connection without exiting")?;
+
+        let replies = match serde_json::from_str::<Value>(&content) {
+            Ok(message) if message["method"] == "exit" => {
+                return match server.shutdown {
+                    true => Ok(()),
+                    false => Err("The client exited without shutting down the server".to_owned()),
+                };
+            }
+            Ok(message) => server.handle(&message),
+            Err(error) => vec![error_response(&Value::Null, PARSE_ERROR, error.to_string())],
+        };
+
+        for reply in replies {
+            write_message(&mut output, &reply)
+                .map_err(|error| format!("Could not write message: {error}"))?;
+        }
+    }
+}
+
+/// Reads the content of a message that is framed by a Content-Length header, or nothing at the end of the input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length"))?;
+    let mut content = vec![0; length];
+    input.read_exact(&mut content)?;
+
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
+
+fn error_response(id: &Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+struct Server {
+    /// The text of the open documents, which takes precedence over the files on disk
+    documents: Rc<RefCell<HashMap<PathBuf, String>>>,
+    loader: Loader,
+    /// The symbols of each open document and its imports from the last time that they loaded without errors,
+    /// which are still used while the document is being edited
+    symbols: HashMap<PathBuf, SymbolTable>,
+    /// Files that problems were reported for, which are cleared once they have none
+    diagnosed: BTreeSet<PathBuf>,
+    shutdown: bool,
+}
+
+impl Server {
+    fn new() -> Self {
+        let documents = Rc::new(RefCell::new(HashMap::<PathBuf, String>::new()));
+        let open = Rc::clone(&documents);
+        let loader = Loader::with_reader(move |path| match open.borrow().get(path) {
+            Some(text) => Ok(text.clone()),
+            None => fs::read_to_string(path),
+        });
+
+        Self {
+            documents,
+            loader,
+            symbols: HashMap::new(),
+            diagnosed: BTreeSet::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Handles a request or a notification and returns the messages to send back
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // The server does not send requests, so there are no responses to handle
+            None => return Vec::new(),
+        };
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => vec![match self.request(method, params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => error_response(id, code, message),
+            }],
+            None => self.notify(method, params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shutdown {
+            return Err((INVALID_REQUEST, "The server is shutting down".to_owned()));
+        }
+
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": FULL_SYNC,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "referencesProvider": true,
+                    "completionProvider": {},
+                },
+                "serverInfo": { "name": "type-lang", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => Ok(self.definition(params).unwrap_or(Value::Null)),
+            "textDocument/hover" => Ok(self.hover(params).unwrap_or(Value::Null)),
+            "textDocument/references" => Ok(Value::Array(self.references(params))),
+            "textDocument/completion" => Ok(Value::Array(self.completion(params))),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method '{method}'"))),
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let document = match params["textDocument"]["uri"].as_str().and_then(path) {
+            Some(document) => document,
+            None => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents
+                    .borrow_mut()
+                    .insert(document, text.to_owned());
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents
+                        .borrow_mut()
+                        .insert(document, text.to_owned());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.borrow_mut().remove(&document);
+                self.symbols.remove(&document);
+            }
+            _ => return Vec::new(),
+        }
+
+        self.diagnose()
+    }
+
+    /// The text of an open document, or of a file on disk otherwise
+    fn source(&self, path: &Path) -> Option<String> {
+        match self.documents.borrow().get(path) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(path).ok(),
+        }
+    }
+
+    /// Loads all open documents and publishes the problems of every file that they load
+    fn diagnose(&mut self) -> Vec<Value> {
+        let mut problems = BTreeMap::<PathBuf, Vec<(Range<usize>, String)>>::new();
+        let mut report = |path: PathBuf, range: Range<usize>, message: String| {
+            let problems = problems.entry(path).or_default();
+            // Files that are imported by several documents are only reported once
+            if !problems.contains(&(range.clone(), message.clone())) {
+                problems.push((range, message));
+            }
+        };
+
+        let documents = self.documents.borrow().keys().cloned().collect::<Vec<_>>();
+        for document in documents {
+            match self.loader.load(&document) {
+                Ok(symbols) => {
+                    for diagnostic in checker::check(&symbols) {
+                        let range = self
+                            .declaration(&diagnostic.path, &diagnostic.ident)
+                            .unwrap_or(0..0);
+                        report(diagnostic.path, range, diagnostic.message);
+                    }
+                    self.symbols.insert(document, symbols);
+                }
+                Err(error) => {
+                    let (path, range) = self.locate(&document, &error);
+                    let message = match error {
+                        LoadError::Parse { error, .. } => error.to_string(),
+                        error => error.to_string(),
+                    };
+                    report(path, range, message);
+                }
+            }
+        }
+
+        let mut notifications = Vec::new();
+        for path in self
+            .diagnosed
+            .iter()
+            .filter(|path| !problems.contains_key(*path))
+        {
+            notifications.push(publish(path, json!([])));
+        }
+        for (path, problems) in &problems {
+            let source = self.source(path).unwrap_or_default();
+            let diagnostics = problems
+                .iter()
+                .map(|(range, message)| {
+                    json!({
+                        "range": lsp_range(&source, range),
+                        "severity": 1,
+                        "source": "type-lang",
+                        "message": message,
+                    })
+                })
+                .collect();
+            notifications.push(publish(path, Value::Array(diagnostics)));
+        }
+        self.diagnosed = problems.into_keys().collect();
+
+        notifications
+    }
+
+    /// The file and the byte range that an error of loading a document is about
+    fn locate(&self, document: &Path, error: &LoadError) -> (PathBuf, Range<usize>) {
+        match error {
+            LoadError::Parse { path, error } => {
+                let source = self.source(path).unwrap_or_default();
+                let offset = line_offset(&source, error.line, error.column);
+                (path.clone(), offset..offset)
+            }
+            LoadError::Duplicate { ident, second, .. } => {
+                let range = self.declaration(second, ident).unwrap_or(0..0);
+                (second.clone(), range)
+            }
+            LoadError::Ambiguous { ident, path, .. } => {
+                let source = self.source(path).unwrap_or_default();
+                let range = Outline::new(&source)
+                    .symbols
+                    .into_iter()
+                    .find(|symbol| symbol.kind == SymbolKind::Reference && *symbol.ident == **ident)
+                    .map_or(0..0, |symbol| symbol.range);
+                (path.clone(), range)
+            }
+            LoadError::Io { path, .. } => {
+                // A missing import is shown at the import of the document, if it is imported directly
+                let source = self.source(document).unwrap_or_default();
+                let directory = document.parent().unwrap_or(Path::new(""));
+                let range = Outline::new(&source)
+                    .imports
+                    .into_iter()
+                    .find(|(import, _)| directory.join(import) == *path)
+                    .map_or(0..0, |(_, range)| range);
+                (document.to_owned(), range)
+            }
+            LoadError::Cycle(_) => (document.to_owned(), 0..0),
+        }
+    }
+
+    /// The byte range of the name of a declaration in a file
+    fn declaration(&self, path: &Path, ident: &str) -> Option<Range<usize>> {
+        let source = self.source(path)?;
+        let symbol = Outline::new(&source)
+            .declaration(split_qualified(ident).1)?
+            .range
+            .clone();
+        Some(symbol)
+    }
+
+    /// The qualified name of the declaration that the type name at the position of a request refers to,
+    /// together with the symbols of the document
+    fn resolve(&self, params: &Value) -> Option<(&SymbolTable, String)> {
+        let document = params["textDocument"]["uri"].as_str().and_then(path)?;
+        let source = self.source(&document)?;
+        let outline = Outline::new(&source);
+        let symbol = outline.symbol_at(offset(&source, &params["position"]))?;
+        let symbols = self.symbols.get(&document)?;
+
+        let ident = match symbol.kind {
+            SymbolKind::Declaration => qualify(outline.package.as_deref(), &symbol.ident),
+            SymbolKind::Reference => symbols
+                .lookup(&symbol.ident, outline.package.as_deref())?
+                .to_owned(),
+            SymbolKind::Parameter => return None,
+        };
+
+        Some((symbols, ident))
+    }
+
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (symbols, ident) = self.resolve(params)?;
+        let path = symbols.path(&ident)?;
+        let source = self.source(path)?;
+        let range = self.declaration(path, &ident)?;
+
+        Some(json!({ "uri": uri(path), "range": lsp_range(&source, &range) }))
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (symbols, ident) = self.resolve(params)?;
+        Some(json!({ "contents": markdown(symbols.get(&ident)?) }))
+    }
+
+    /// Finds the references in all files that the open documents load together with the declaration
+    fn references(&self, params: &Value) -> Vec<Value> {
+        let (symbols, ident) = match self.resolve(params) {
+            Some(resolved) => resolved,
+            None => return Vec::new(),
+        };
+        let declared_in = match symbols.path(&ident) {
+            Some(path) => path,
+            None => return Vec::new(),
+        };
+        let include_declaration = params["context"]["includeDeclaration"]
+            .as_bool()
+            .unwrap_or(true);
+
+        // Names are looked up in the symbols that a file was loaded with, since unqualified names depend on them
+        let mut files = BTreeMap::new();
+        for symbols in self.symbols.values() {
+            if symbols.path(&ident) == Some(declared_in) {
+                for file in symbols.files() {
+                    files.entry(file.as_path()).or_insert(symbols);
+                }
+            }
+        }
+
+        let mut locations = Vec::new();
+        for (file, symbols) in files {
+            let source = match self.source(file) {
+                Some(source) => source,
+                None => continue,
+            };
+            let outline = Outline::new(&source);
+            let package = outline.package.as_deref();
+
+            for symbol in &outline.symbols {
+                let found = match symbol.kind {
+                    SymbolKind::Declaration => {
+                        include_declaration
+                            && file == declared_in
+                            && qualify(package, &symbol.ident) == ident
+                    }
+                    SymbolKind::Reference => {
+                        symbols.lookup(&symbol.ident, package) == Some(ident.as_str())
+                    }
+                    SymbolKind::Parameter => false,
+                };
+                if found {
+                    locations.push(
+                        json!({ "uri": uri(file), "range": lsp_range(&source, &symbol.range) }),
+                    );
+                }
+            }
+        }
+
+        locations
+    }
+
+    /// Suggests the built-in types, the declared types and the type parameters of the declaration at the position
+    fn completion(&self, params: &Value) -> Vec<Value> {
+        let mut items = BUILTIN_TYPES
+            .iter()
+            .map(|ty| json!({ "label": ty, "kind": COMPLETION_KEYWORD }))
+            .collect::<Vec<_>>();
+
+        let document = match params["textDocument"]["uri"].as_str().and_then(path) {
+            Some(document) => document,
+            None => return items,
+        };
+        let source = self.source(&document).unwrap_or_default();
+        let outline = Outline::new(&source);
+        let package = outline.package.as_deref();
+
+        match self.symbols.get(&document) {
+            Some(symbols) => {
+                for declaration in symbols.declarations() {
+                    let qualified = declaration.qualified_ident();
+                    // Types are suggested by the shortest name that refers to them
+                    let label = match symbols.lookup(declaration.ident(), package) {
+                        Some(found) if found == qualified => declaration.ident(),
+                        _ => &qualified,
+                    };
+                    items.push(json!({
+                        "label": label,
+                        "kind": COMPLETION_STRUCT,
+                        "detail": qualified,
+                        "documentation": markdown(declaration),
+                    }));
+                }
+            }
+            // Until the document loads without errors, only the types declared in it are known
+            None => {
+                for symbol in &outline.symbols {
+                    if symbol.kind == SymbolKind::Declaration {
+                        items.push(json!({ "label": symbol.ident, "kind": COMPLETION_STRUCT }));
+                    }
+                }
+            }
+        }
+
+        let offset = offset(&source, &params["position"]);
+        let mut parameters = Vec::new();
+        for symbol in outline
+            .symbols
+            .iter()
+            .take_while(|symbol| symbol.range.start < offset)
+        {
+            match symbol.kind {
+                SymbolKind::Declaration => parameters.clear(),
+                SymbolKind::Parameter if !parameters.contains(&&symbol.ident) => {
+                    parameters.push(&symbol.ident)
+                }
+                _ => {}
+            }
+        }
+        for parameter in parameters {
+            items.push(json!({ "label": parameter, "kind": COMPLETION_TYPE_PARAMETER }));
+        }
+
+        items
+    }
+}
+
+/// Shows a declaration as a code block
+fn markdown(declaration: &Declaration) -> Value {
+    let declaration = formatter::format_declaration(declaration);
+    json!({ "kind": "markdown", "value": format!("```type\n{declaration}\n```") })
+}
+
+fn publish(path: &Path, diagnostics: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri(path), "diagnostics": diagnostics },
+    })
+}
+
+/// The path of a `file://` URI, with percent-encoded bytes decoded
+fn path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+    while index < encoded.len() {
+        let byte = encoded
+            .get(index + 1..index + 3)
+            .filter(|_| encoded[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(encoded[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// The `file://` URI of an absolute path
+fn uri(path: &Path) -> String {
+    let mut uri = "file://".to_owned();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    uri
+}
+
+/// The position of a byte offset, whose character counts UTF-16 code units like the protocol does by default
+fn position(source: &str, offset: usize) -> Value {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn lsp_range(source: &str, range: &Range<usize>) -> Value {
+    json!({ "start": position(source, range.start), "end": position(source, range.end) })
+}
+
+/// The byte offset of a position, which is clamped to the end of its line
+fn offset(source: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line)
+        .map(str::len)
+        .sum::<usize>();
+
+    let mut units = 0;
+    for (index, ch) in source[line_start..].char_indices() {
+        if units >= character || ch == '\n' {
+            return line_start + index;
+        }
+        units += ch.len_utf16();
+    }
+
+    source.len()
+}
+
+/// The byte offset of a line and a byte column of a syntax error, both starting at 1
+fn line_offset(source: &str, line: usize, column: usize) -> usize {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+
+    let mut offset = (line_start + column.saturating_sub(1)).min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use serde_json::{json, Value};
+
+    use super::{read_message, write_message, Server};
+
+    const USER: &str = r#"import "billing.type"
+
+type User {
+	name: String
+	invoice: billing.Invoice
+	status: Status
+}
+"#;
+
+    const BILLING: &str = "package billing
+
+type Invoice {
+	status: Status
+}
+
+type Status { paid: Bool }
+";
+
+    fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": uri, "languageId": "type", "version": 1, "text": text },
+            },
+        }))
+    }
+
+    fn request(server: &mut Server, method: &str, params: Value) -> Value {
+        let mut replies = server
+            .handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }));
+        assert_eq!(replies.len(), 1);
+        replies.remove(0)["result"].take()
+    }
+
+    fn at(uri: &str, line: usize, character: usize) -> Value {
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    fn range(line: usize, start: usize, end: usize) -> Value {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    }
+
+    #[test]
+    fn frame_messages() {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "result": "ü" });
+        let mut written = Vec::new();
+        write_message(&mut written, &message).unwrap();
+
+        assert!(written.starts_with(b"Content-Length: 38\r\n\r\n"));
+        let read = read_message(&mut Cursor::new(written)).unwrap().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&read).unwrap(), message);
+        assert_eq!(read_message(&mut Cursor::new(Vec::new())).unwrap(), None);
+    }
+
+    #[test]
+    fn publish_diagnostics() {
+        let mut server = Server::new();
+
+        let notifications = open(
+            &mut server,
+            "file:///schema/user.type",
+            "type User {\n\tname: Strin\n}\n",
+        );
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(
+            notifications[0]["params"]["uri"],
+            "file:///schema/user.type"
+        );
+        let diagnostic = &notifications[0]["params"]["diagnostics"][0];
+        assert_eq!(
+            diagnostic["message"],
+            "Unknown type 'Strin' in the field 'name' of 'User'"
+        );
+        assert_eq!(diagnostic["range"], range(0, 5, 9));
+
+        let notifications = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///schema/user.type", "version": 2 },
+                "contentChanges": [{ "text": "type User {\n\tname String\n}\n" }],
+            },
+        }));
+        let diagnostic = &notifications[0]["params"]["diagnostics"][0];
+        assert_eq!(
+            diagnostic["message"],
+            "Unexpected 'String' at line 2, column 7"
+        );
+        assert_eq!(diagnostic["range"], range(1, 6, 6));
+
+        let notifications = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///schema/user.type", "version": 3 },
+                "contentChanges": [{ "text": "type User {\n\tname: String\n}\n" }],
+            },
+        }));
+        assert_eq!(notifications[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn navigate_between_files() {
+        let mut server = Server::new();
+        open(&mut server, "file:///schema/billing.type", BILLING);
+        open(&mut server, "file:///schema/user.type", USER);
+
+        assert_eq!(
+            request(
+                &mut server,
+                "textDocument/definition",
+                at("file:///schema/user.type", 4, 20)
+            ),
+            json!({ "uri": "file:///schema/billing.type", "range": range(2, 5, 12) })
+        );
+        assert_eq!(
+            request(
+                &mut server,
+                "textDocument/definition",
+                at("file:///schema/billing.type", 3, 10)
+            ),
+            json!({ "uri": "file:///schema/billing.type", "range": range(6, 5, 11) })
+        );
+
+        let hover = request(
+            &mut server,
+            "textDocument/hover",
+            at("file:///schema/user.type", 5, 10),
+        );
+        assert_eq!(
+            hover["contents"]["value"],
+            "```type\ntype Status {\n\tpaid: Bool\n}\n```"
+        );
+
+        let references = request(
+            &mut server,
+            "textDocument/references",
+            at("file:///schema/billing.type", 6, 7),
+        );
+        assert_eq!(
+            references,
+            json!([
+                { "uri": "file:///schema/billing.type", "range": range(3, 9, 15) },
+                { "uri": "file:///schema/billing.type", "range": range(6, 5, 11) },
+                { "uri": "file:///schema/user.type", "range": range(5, 9, 15) },
+            ])
+        );
+    }
+
+    #[test]
+    fn complete_type_names() {
+        let mut server = Server::new();
+        open(&mut server, "file:///schema/billing.type", BILLING);
+        open(&mut server, "file:///schema/user.type", USER);
+        open(
+            &mut server,
+            "file:///schema/page.type",
+            "type Page<T> {\n\titems: [T]\n}\n",
+        );
+
+        let labels = |items: Value| {
+            items
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["label"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let items = labels(request(
+            &mut server,
+            "textDocument/completion",
+            at("file:///schema/user.type", 3, 8),
+        ));
+        assert!(items.contains(&"String".to_owned()));
+        assert!(items.contains(&"Invoice".to_owned()));
+        assert!(items.contains(&"User".to_owned()));
+        assert!(!items.contains(&"T".to_owned()));
+
+        let items = labels(request(
+            &mut server,
+            "textDocument/completion",
+            at("file:///schema/page.type", 1, 9),
+        ));
+        assert!(items.contains(&"Page".to_owned()));
+        assert!(items.contains(&"T".to_owned()));
+    }
+
+    #[test]
+    fn reject_requests_after_shutdown() {
+        let mut server = Server::new();
+        assert_eq!(request(&mut server, "shutdown", Value::Null), Value::Null);
+
+        let replies =
+            server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover" }));
+        assert_eq!(replies[0]["error"]["code"], -32600);
+    }
+}
@@ -1,54 +1,420 @@
 use std::{
     env,
-    fs::File,
-    io::Write,
-    io::{self, Read},
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process,
 };
 
+extern crate serde;
+extern crate serde_json;
+extern crate toml;
 extern crate type_lib;
 use type_lib::{
-    generator::{gleam, rust, Generator},
-    parser::Parser,
+    checker, formatter,
+    generator::split_header,
+    loader::{Loader, SymbolTable},
 };
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
+mod cli;
+mod config;
+mod diff;
+mod lsp;
+mod manifest;
+mod watch;
+use cli::{is_stdio, Command, Language, USAGE};
+use config::{Config, CONFIG_FILE};
+use manifest::Manifest;
 
-    if args.len() < 2 {
-        eprintln!("Usage: {} <file_path>", args[0]);
-        std::process::exit(1);
-    }
+fn main() {
+    let command = match cli::parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
 
-    let file_path = &args[1];
-    let file_path = match file_path.strip_suffix(".type") {
-        Some(f) => f,
-        None => file_path,
+    let result = match command {
+        Command::Generate {
+            languages,
+            out,
+            input,
+            config,
+            check,
+        } => generate(languages, out, input, config, check),
+        Command::Watch {
+            languages,
+            out,
+            input,
+            config,
+        } => watch::watch(languages, out, input, config),
+        Command::Check { input, config } => check(input, config),
+        Command::Fmt {
+            input,
+            config,
+            check,
+            write,
+        } => fmt(input, config, check, write),
+        Command::Lsp => lsp::serve(),
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
     };
 
-    let mut file = File::open(format!("{file_path}.type"))?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    if let Err(error) = result {
+        eprintln!("{error}");
+        process::exit(1);
+    }
+}
+
+/// Reads the given configuration file, or the one of the current directory if there is one
+fn read_config(path: Option<PathBuf>) -> Result<Config, String> {
+    match path {
+        Some(path) => Config::read(&path),
+        None => Config::discover().map(Option::unwrap_or_default),
+    }
+}
+
+/// The schema given on the command line, or the inputs of the configuration otherwise
+fn inputs(input: Option<PathBuf>, config: &Config) -> Result<Vec<PathBuf>, String> {
+    if let Some(input) = input {
+        return Ok(vec![input]);
+    }
+
+    let inputs = config.inputs()?;
+    match inputs.is_empty() {
+        true => Err(format!(
+            "Missing schema file, either pass one or configure inputs in {CONFIG_FILE}"
+        )),
+        false => Ok(inputs),
+    }
+}
+
+/// A loader that reads a schema given as `-` from stdin and all other schemas from the file system
+fn stdin_loader(inputs: &[PathBuf]) -> Result<Loader, String> {
+    if !inputs.iter().any(|input| is_stdio(input)) {
+        return Ok(Loader::new());
+    }
+
+    let source = read_stdin()?;
+    Ok(Loader::with_reader(move |path| match is_stdio(path) {
+        true => Ok(source.clone()),
+        false => fs::read_to_string(path),
+    }))
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut source = String::new();
+    io::stdin()
+        .read_to_string(&mut source)
+        .map_err(|error| format!("Could not read stdin: {error}"))?;
+    Ok(source)
+}
+
+/// Loads the schemas with their imports and makes sure that they are valid
+fn load(loader: &Loader, inputs: &[PathBuf]) -> Result<SymbolTable, String> {
+    let symbols = loader.load_all(inputs).map_err(|error| error.to_string())?;
+
+    let diagnostics = checker::check(&symbols);
+    if !diagnostics.is_empty() {
+        let diagnostics = diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        return Err(diagnostics.join("\n"));
+    }
+
+    Ok(symbols)
+}
+
+/// Generates all languages, or only compares the generated files to the ones on disk with `check`
+fn generate(
+    languages: Option<Vec<Language>>,
+    out: Option<PathBuf>,
+    input: Option<PathBuf>,
+    config: Option<PathBuf>,
+    check: bool,
+) -> Result<(), String> {
+    let config = read_config(config)?;
+    let inputs = inputs(input, &config)?;
+    let symbols = load(&stdin_loader(&inputs)?, &inputs)?;
+    let languages = languages.unwrap_or_else(|| config.languages());
 
-    let generators: Vec<Box<dyn Generator>> = vec![
-        gleam::GleamTypeGenerator::boxed(),
-        rust::RustTypeGenerator::boxed(),
-    ];
+    if out.as_deref().is_some_and(is_stdio) {
+        // Files are labeled with their path relative to the output directory
+        let outputs = render(&languages, Some(Path::new("")), &config, &symbols, &inputs);
+        let files = outputs
+            .iter()
+            .flat_map(|output| &output.files)
+            .map(|(path, content)| format!("==> {} <==\n{}\n", path.display(), content.trim_end()))
+            .collect::<Vec<_>>();
+        print!("{}", files.join("\n"));
+        return Ok(());
+    }
+
+    let outputs = render(&languages, out.as_deref(), &config, &symbols, &inputs);
+    if check {
+        return check_files(&outputs, &schema_hash(&symbols));
+    }
 
-    for mut generator in generators {
-        let mut parser = Parser::new(&contents);
-        for ty in parser.parse() {
-            generator.add_type(&ty);
+    for output in &outputs {
+        for (path, content) in &output.files {
+            write_file(path, content)
+                .map_err(|error| format!("Could not write '{}': {error}", path.display()))?;
+            println!("Wrote {}", path.display());
+        }
+        clean(output)?;
+    }
+
+    Ok(())
+}
+
+/// The generated files of a language
+struct Output {
+    language: Language,
+    directory: PathBuf,
+    /// The content of each file by its path, which includes the output directory
+    files: Vec<(PathBuf, String)>,
+}
+
+impl Output {
+    fn manifest(&self) -> Manifest {
+        let names = self
+            .files
+            .iter()
+            .map(|(path, _)| path.strip_prefix(&self.directory).unwrap_or(path))
+            .map(|path| path.to_str().unwrap_or_default());
+        Manifest::new(names)
+    }
+
+    /// Files that were generated by a previous run, but not by this one
+    fn orphans(&self) -> Result<Vec<PathBuf>, String> {
+        let previous = Manifest::read(&self.directory, self.language).map_err(|error| {
+            let path = Manifest::path(&self.directory, self.language);
+            format!("Could not read '{}': {error}", path.display())
+        })?;
+
+        let orphans = previous
+            .orphans(&self.manifest())
+            .map(|name| self.directory.join(name))
+            .collect();
+        Ok(orphans)
+    }
+}
+
+/// Removes the files of types that are no longer generated and records the generated files for the next run
+fn clean(output: &Output) -> Result<(), String> {
+    for orphan in output.orphans()? {
+        match fs::remove_file(&orphan) {
+            Ok(()) => println!("Removed {}", orphan.display()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(format!("Could not remove '{}': {error}", orphan.display())),
+        }
+
+        // Folders of packages without any remaining types are removed as well
+        let mut parent = orphan.parent();
+        while let Some(directory) = parent.filter(|directory| *directory != output.directory) {
+            if fs::remove_dir(directory).is_err() {
+                break;
+            }
+            parent = directory.parent();
+        }
+    }
+
+    // Languages without any generated files have not created the output directory
+    fs::create_dir_all(&output.directory)
+        .and_then(|()| output.manifest().write(&output.directory, output.language))
+        .map_err(|error| {
+            let path = Manifest::path(&output.directory, output.language);
+            format!("Could not write '{}': {error}", path.display())
+        })
+}
+
+/// The hash of the loaded schemas that generated files are stamped with
+fn schema_hash(symbols: &SymbolTable) -> String {
+    format!("{:016x}", symbols.hash())
+}
+
+/// The content of a generated file after its header
+fn body(file: &str) -> &str {
+    split_header(file).map_or(file, |(_, body)| body)
+}
+
+/// Generates the files of all languages in memory, together with the directory that they are written to
+fn render(
+    languages: &[Language],
+    out: Option<&Path>,
+    config: &Config,
+    symbols: &SymbolTable,
+    inputs: &[PathBuf],
+) -> Vec<Output> {
+    let mut outputs = Vec::new();
+    let hash = schema_hash(symbols);
+    let source = inputs
+        .iter()
+        .map(|input| match is_stdio(input) {
+            true => "stdin".to_owned(),
+            false => input.display().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    for &language in languages {
+        let mut generator = config.generator(language);
+        for declaration in symbols.declarations() {
+            generator.add_declaration(declaration);
         }
 
         let ext = generator.file_extension();
-        for file in generator.output_dyn() {
-            let output_file_name = format!("{}.{ext}", file.name);
-            let mut output_file = File::create(&output_file_name)?;
+        // An output directory on the command line replaces all configured ones
+        let directory = match out {
+            Some(out) => out.join(language.name()),
+            None => config.output_dir(language),
+        };
+        let files = generator
+            .output_dyn()
+            .map(|file| {
+                let path = directory.join(format!("{}.{ext}", file.name));
+                let header = generator.header(&source, &hash);
+                (path, format!("{header}\n{}\n", file.content))
+            })
+            .collect();
 
-            writeln!(output_file, "{}", file.content)?;
-            println!("Decoder written to {}", output_file_name);
+        outputs.push(Output {
+            language,
+            directory,
+            files,
+        });
+    }
+
+    outputs
+}
+
+/// Prints a diff for each generated file that differs from the one on disk, and lists files that would be removed
+///
+/// Files that differ although their header has the current schema hash were edited by hand instead of being outdated.
+fn check_files(outputs: &[Output], hash: &str) -> Result<(), String> {
+    let mut stale = 0;
+    let mut edited = 0;
+
+    for output in outputs {
+        for orphan in output.orphans()? {
+            if orphan.exists() {
+                println!("{} is no longer generated", orphan.display());
+                stale += 1;
+            }
         }
     }
 
-    Ok(())
+    let files = outputs.iter().flat_map(|output| &output.files);
+    for (path, content) in files {
+        let on_disk = match fs::read_to_string(path) {
+            Ok(on_disk) => on_disk,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("Could not read '{}': {error}", path.display())),
+        };
+        // Only the header changes when an unrelated part of the schema changed
+        if body(&on_disk) == body(content) {
+            continue;
+        }
+
+        let name = path.display().to_string();
+        let diff = diff::unified_diff(&name, &format!("{name} (generated)"), &on_disk, content);
+        print!("{diff}");
+        match split_header(&on_disk) {
+            Some((stamp, _)) if stamp == hash => {
+                println!("{name} was edited by hand");
+                edited += 1;
+            }
+            _ => stale += 1,
+        }
+    }
+
+    let mut problems = Vec::new();
+    if stale > 0 {
+        problems.push(format!("{stale} generated files are out of date"));
+    }
+    if edited > 0 {
+        problems.push(format!("{edited} generated files were edited by hand"));
+    }
+
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(format!(
+            "{}, run type-lang generate to update them",
+            problems.join(" and ")
+        )),
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(content.as_bytes())
+}
+
+fn check(input: Option<PathBuf>, config: Option<PathBuf>) -> Result<(), String> {
+    let config = read_config(config)?;
+    let inputs = inputs(input, &config)?;
+    load(&stdin_loader(&inputs)?, &inputs).map(|_| ())
+}
+
+fn fmt(
+    input: Option<PathBuf>,
+    config: Option<PathBuf>,
+    check: bool,
+    write: bool,
+) -> Result<(), String> {
+    if !check && !write {
+        let input = input.ok_or("Missing schema file")?;
+        print!("{}", format_file(&input)?.1);
+        return Ok(());
+    }
+
+    let config = read_config(config)?;
+    let inputs = inputs(input, &config)?;
+
+    let mut unformatted = 0;
+    for input in &inputs {
+        let (source, formatted) = format_file(input)?;
+        if source == formatted {
+            continue;
+        }
+
+        if write {
+            write_file(input, &formatted)
+                .map_err(|error| format!("Could not write '{}': {error}", input.display()))?;
+        } else {
+            let name = input.display().to_string();
+            let diff =
+                diff::unified_diff(&name, &format!("{name} (formatted)"), &source, &formatted);
+            print!("{diff}");
+            unformatted += 1;
+        }
+    }
+
+    match unformatted {
+        0 => Ok(()),
+        _ => Err(format!(
+            "{unformatted} schema files are not formatted, run type-lang fmt --write to format them"
+        )),
+    }
+}
+
+/// Reads a schema and returns its source together with its canonical formatting
+fn format_file(input: &Path) -> Result<(String, String), String> {
+    let source = match is_stdio(input) {
+        true => read_stdin()?,
+        false => fs::read_to_string(input)
+            .map_err(|error| format!("Could not read '{}': {error}", input.display()))?,
+    };
+    let formatted = formatter::format_source(&source)
+        .map_err(|error| format!("{}: {error}", input.display()))?;
+
+    Ok((source, formatted))
 }
@@ -0,0 +1,126 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use cli::Language;
+
+const HEADER: &str =
+    "# Files generated by type-lang, which are removed once their types are no longer generated";
+
+/// The files that were generated for a language, which is kept in the output directory
+/// to find the files of removed types on the next run
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Paths relative to the output directory
+    files: BTreeSet<String>,
+}
+
+impl Manifest {
+    pub fn new<'a>(files: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            files: files.into_iter().map(str::to_owned).collect(),
+        }
+    }
+
+    /// The manifest file of a language in its output directory
+    pub fn path(directory: &Path, language: Language) -> PathBuf {
+        directory.join(format!(".type-manifest-{}", language.name()))
+    }
+
+    /// Reads the manifest of a previous run, which is empty if nothing was generated yet
+    pub fn read(directory: &Path, language: Language) -> io::Result<Self> {
+        match fs::read_to_string(Self::path(directory, language)) {
+            Ok(source) => Self::parse(&source),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn write(&self, directory: &Path, language: Language) -> io::Result<()> {
+        fs::write(Self::path(directory, language), self.to_string())
+    }
+
+    /// Parses the files of a manifest, which must all be inside of the output directory,
+    /// as the files are removed once they are no longer generated
+    fn parse(source: &str) -> io::Result<Self> {
+        let files = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        for file in files.clone() {
+            let is_nested = Path::new(file)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+            if !is_nested {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("'{file}' is not a path inside of the output directory"),
+                ));
+            }
+        }
+
+        Ok(Self::new(files))
+    }
+
+    /// Files of this manifest that are missing from a newer one
+    pub fn orphans<'a>(&'a self, current: &'a Manifest) -> impl Iterator<Item = &'a str> {
+        self.files.difference(&current.files).map(String::as_str)
+    }
+}
+
+impl Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for file in &self.files {
+            writeln!(f, "{file}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Manifest;
+
+    #[test]
+    fn read_written_manifest() {
+        let manifest = Manifest::new(["user.rs", "billing/status.rs", "mod.rs"]);
+        let written = manifest.to_string();
+
+        assert!(written.ends_with("\nbilling/status.rs\nmod.rs\nuser.rs\n"));
+        assert_eq!(Manifest::parse(&written).unwrap(), manifest);
+    }
+
+    #[test]
+    fn reject_paths_outside_of_the_output_directory() {
+        for file in [
+            "../../victim.txt",
+            "billing/../../user.rs",
+            "/etc/passwd",
+            "./user.rs",
+        ] {
+            let source = format!("user.rs\n{file}\n");
+            let error = Manifest::parse(&source).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                format!("'{file}' is not a path inside of the output directory")
+            );
+        }
+    }
+
+    #[test]
+    fn find_orphans() {
+        let previous = Manifest::new(["user.rs", "account.rs", "billing/status.rs"]);
+        let current = Manifest::new(["user.rs", "billing/invoice.rs"]);
+
+        assert_eq!(
+            previous.orphans(&current).collect::<Vec<_>>(),
+            vec!["account.rs", "billing/status.rs"]
+        );
+    }
+}
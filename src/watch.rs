@@ -0,0 +1,115 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    slice, thread,
+    time::{Duration, SystemTime},
+};
+
+use type_lib::loader::Loader;
+
+use cli::Language;
+use config::{Config, CONFIG_FILE};
+use {clean, inputs, load, read_config, render, write_file};
+
+/// How often the watched schema files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Regenerates all languages whenever one of the schemas or their imports changes, until the process is interrupted
+///
+/// The configuration is read again for each run, so that changed options and files that newly match the inputs are picked up.
+pub fn watch(
+    languages: Option<Vec<Language>>,
+    out: Option<PathBuf>,
+    input: Option<PathBuf>,
+    config: Option<PathBuf>,
+) -> Result<(), String> {
+    // The default configuration file is watched even if it does not exist yet, so that creating it triggers a run
+    let config_file = config.clone().unwrap_or_else(|| PathBuf::from(CONFIG_FILE));
+    // The loader keeps the parsed schemas between runs, so that only changed files are parsed again
+    let loader = Loader::new();
+    let mut watched = Vec::new();
+
+    loop {
+        let config = match read_config(config.clone()) {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!("{error}");
+                println!("Watching {} for changes", config_file.display());
+                wait_for_change(slice::from_ref(&config_file), || false);
+                continue;
+            }
+        };
+        let languages = languages.clone().unwrap_or_else(|| config.languages());
+        let resolved = inputs(input.clone(), &config);
+        let result = resolved
+            .clone()
+            .and_then(|inputs| regenerate(&loader, &inputs, &languages, out.as_deref(), &config));
+        match result {
+            Ok(files) => watched = files,
+            // Files that failed to load keep being watched, so that fixing them triggers the next run
+            Err(error) => {
+                eprintln!("{error}");
+                for input in resolved.iter().flatten() {
+                    if !watched.contains(input) {
+                        watched.push(input.clone());
+                    }
+                }
+            }
+        }
+
+        println!("Watching {} schema files for changes", watched.len());
+        let mut paths = watched.clone();
+        paths.push(config_file.clone());
+        // Files that are created or removed can change which files match the input patterns
+        wait_for_change(&paths, || inputs(input.clone(), &config) != resolved);
+    }
+}
+
+/// Generates all files, writes the ones whose content changed and removes the ones that are no longer generated,
+/// returning the schema files that were loaded
+fn regenerate(
+    loader: &Loader,
+    inputs: &[PathBuf],
+    languages: &[Language],
+    out: Option<&Path>,
+    config: &Config,
+) -> Result<Vec<PathBuf>, String> {
+    let symbols = load(loader, inputs)?;
+
+    for output in render(languages, out, config, &symbols, inputs) {
+        for (path, content) in &output.files {
+            // The header is compared as well, so that the schema hash on disk is always the current one
+            if fs::read_to_string(path).ok().as_deref() == Some(content.as_str()) {
+                continue;
+            }
+
+            write_file(path, content)
+                .map_err(|error| format!("Could not write '{}': {error}", path.display()))?;
+            println!("Wrote {}", path.display());
+        }
+        clean(&output)?;
+    }
+
+    Ok(symbols.files().to_vec())
+}
+
+/// Blocks until a file was modified, created or removed, or until the given check reports a change
+fn wait_for_change(paths: &[PathBuf], changed: impl Fn() -> bool) {
+    let initial = modified_times(paths);
+    while modified_times(paths) == initial && !changed() {
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn modified_times(paths: &[PathBuf]) -> BTreeMap<&Path, Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| {
+            let modified = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (path.as_path(), modified)
+        })
+        .collect()
+}
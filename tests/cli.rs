@@ -0,0 +1,50 @@
+//! Runs the `type-lang` executable on schemas in temporary directories.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A temporary directory that the command is run in
+fn project(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("type-lang-cli-{name}-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn run(directory: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_type-lang"))
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn generate_schema_without_output() {
+    let directory = project("empty");
+    fs::write(directory.join("empty.type"), "").unwrap();
+
+    let output = run(
+        &directory,
+        &[
+            "generate",
+            "--lang",
+            "gleam",
+            "--out",
+            "out/gen",
+            "empty.type",
+        ],
+    );
+    let manifest = directory.join("out/gen/gleam/.type-manifest-gleam");
+    let written = manifest.is_file();
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(written);
+}
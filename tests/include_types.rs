@@ -0,0 +1,50 @@
+extern crate serde;
+extern crate serde_json;
+extern crate type_lang;
+
+mod types {
+    type_lang::include_types!("tests/fixtures/user.type");
+}
+
+mod pages {
+    type_lang::include_types!("tests/fixtures/page.type");
+}
+
+use types::{billing, Tag, User};
+
+#[test]
+fn expand_through_the_reexport() {
+    let user = User {
+        name: "Ferris".to_owned(),
+        tags: vec![Tag {
+            label: "crab".to_owned(),
+        }],
+        invoices: vec![billing::Invoice {
+            owner: None,
+            status: billing::Status("paid".to_owned()),
+        }],
+    };
+
+    assert_eq!(user.clone(), user);
+    assert_eq!(User::default().invoices, Vec::new());
+}
+
+/// A type argument that does not implement `Default`, which generic structs only derive for arguments that do
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+struct Cursor(u32);
+
+#[test]
+fn expand_generic_types() {
+    let page = pages::Page {
+        items: vec![Cursor(1), Cursor(2)],
+        next: None,
+    };
+    let json = serde_json::to_string(&page).unwrap();
+
+    assert_eq!(json, r#"{"items":[1,2],"next":null}"#);
+    assert_eq!(
+        serde_json::from_str::<pages::Page<Cursor>>(&json).unwrap(),
+        page
+    );
+    assert_eq!(pages::Page::<String>::default().items, Vec::<String>::new());
+}
@@ -0,0 +1,191 @@
+//! Includes the Rust types of `.type` schemas at compile time, as an alternative to generating them in build.rs.
+
+use std::{env, path::Path};
+
+use proc_macro::{TokenStream, TokenTree};
+use type_lib::build::Config;
+
+/// Expands to the types that the Rust generator produces for a schema and its imports.
+///
+/// The path is relative to the directory of the crate's Cargo.toml. Since the generated types refer to each other
+/// through `super::`, the macro is invoked at the root of a module:
+///
+/// ```ignore
+/// mod types {
+///     type_lang::include_types!("schema/user.type");
+/// }
+/// ```
+///
+/// Errors in the schema are reported as compile errors at the invocation.
+#[proc_macro]
+pub fn include_types(input: TokenStream) -> TokenStream {
+    let expanded = path(input).and_then(|path| {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").map_err(|error| error.to_string())?;
+        expand(&Path::new(&manifest_dir).join(path))
+    });
+
+    match expanded {
+        Ok(code) => code
+            .parse()
+            .unwrap_or_else(|error| compile_error(&format!("{error}"))),
+        Err(error) => compile_error(&error),
+    }
+}
+
+/// The path of the schema, which is the only argument of the macro
+fn path(input: TokenStream) -> Result<String, String> {
+    let mut tokens = input.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(literal)), None) => {
+            let literal = literal.to_string();
+            string_literal(&literal)
+                .ok_or_else(|| format!("Expected the path of a schema, found {literal}"))
+        }
+        _ => Err(
+            "Expected the path of a schema, e.g. include_types!(\"schema/user.type\")".to_owned(),
+        ),
+    }
+}
+
+/// The value of a string literal or a raw string literal, which is `None` for all other literals
+fn string_literal(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let delimiter = "#".repeat(hashes);
+        return raw
+            .strip_prefix(&format!("{delimiter}\""))?
+            .strip_suffix(&format!("\"{delimiter}"))
+            .map(str::to_owned);
+    }
+
+    let content = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            escaped @ ('\\' | '\'' | '"') => value.push(escaped),
+            'x' => {
+                let digits = [chars.next()?, chars.next()?].iter().collect::<String>();
+                let code = u8::from_str_radix(&digits, 16).ok().filter(u8::is_ascii)?;
+                value.push(code as char);
+            }
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let mut digits = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        digit => digits.push(digit),
+                    }
+                }
+                let code = u32::from_str_radix(&digits.replace('_', ""), 16).ok()?;
+                value.push(char::from_u32(code)?);
+            }
+            // A line break after a backslash is skipped together with the indentation of the next line
+            '\n' => while chars.next_if(|ch| ch.is_whitespace()).is_some() {},
+            _ => return None,
+        }
+    }
+
+    Some(value)
+}
+
+/// Generates the types of a schema, followed by includes of all loaded schemas, so that changing
+/// one of them recompiles the crate
+fn expand(path: &Path) -> Result<String, String> {
+    let module = Config::new()
+        .file(path)
+        .generate()
+        .map_err(|error| error.to_string())?;
+
+    let mut code = module.code;
+    for file in module.files {
+        code.push_str(&format!(
+            "\nconst _: &[u8] = include_bytes!({:?});",
+            file.display().to_string()
+        ));
+    }
+    Ok(code)
+}
+
+/// Tokens are parsed with the span of the invocation, so the error points at the macro call
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!({message:?});").parse().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{expand, string_literal};
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn include_loaded_schemas() {
+        let code = expand(&fixture("user.type")).unwrap();
+
+        assert!(code.contains("pub struct User {"));
+        assert!(code.ends_with(&format!(
+            "\nconst _: &[u8] = include_bytes!({:?});",
+            fixture("user.type").display().to_string()
+        )));
+    }
+
+    #[test]
+    fn report_parse_errors() {
+        let error = expand(&fixture("invalid.type")).unwrap_err();
+
+        assert!(error.contains("invalid.type"), "{error}");
+    }
+
+    #[test]
+    fn report_missing_schemas() {
+        let error = expand(&fixture("missing.type")).unwrap_err();
+
+        assert!(error.starts_with("Could not read "), "{error}");
+    }
+
+    #[test]
+    fn parse_string_literals() {
+        assert_eq!(
+            string_literal(r#""schema/user.type""#).as_deref(),
+            Some("schema/user.type")
+        );
+        assert_eq!(
+            string_literal(r#""schema\\user.type""#).as_deref(),
+            Some("schema\\user.type")
+        );
+        assert_eq!(
+            string_literal(r#""schema/\u{75}ser\x2Etype""#).as_deref(),
+            Some("schema/user.type")
+        );
+        assert_eq!(
+            string_literal(r#"r"C:\schema\user.type""#).as_deref(),
+            Some("C:\\schema\\user.type")
+        );
+        assert_eq!(
+            string_literal(r##"r#"schema/"quoted".type"#"##).as_deref(),
+            Some("schema/\"quoted\".type")
+        );
+
+        for literal in [r#"b"user.type""#, "42", r#""user.type"suffix"#, r#""\q""#] {
+            assert_eq!(string_literal(literal), None, "{literal}");
+        }
+    }
+}
@@ -0,0 +1,20 @@
+mod types {
+    type_lang_macros::include_types!("tests/fixtures/user.type");
+}
+
+use types::{Email, Tag, User};
+
+#[test]
+fn expand_to_generated_types() {
+    let user = User {
+        name: "Ferris".to_owned(),
+        nickname: None,
+        tags: vec![Tag {
+            label: "crab".to_owned(),
+        }],
+        email: Email("ferris@example.com".to_owned()),
+    };
+
+    assert_eq!(user.clone(), user);
+    assert_eq!(User::default().tags, Vec::new());
+}
@@ -0,0 +1,275 @@
+//! Generates Rust types from schemas in build scripts.
+//!
+//! ```no_run
+//! // In the `main` function of build.rs
+//! type_lib::build::Config::new()
+//!     .input("schema/*.type")
+//!     .compile()
+//!     .unwrap();
+//! ```
+//!
+//! The generated types are written into a single file in `OUT_DIR`, which is included with
+//!
+//! ```ignore
+//! mod types {
+//!     include!(concat!(env!("OUT_DIR"), "/types.rs"));
+//! }
+//! ```
+
+use std::{
+    env,
+    fmt::{self, Display},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    checker::{self, Diagnostic},
+    generator::{rust::RustTypeGenerator, Generator},
+    inputs::{self, base_dir, InputError},
+    loader::{LoadError, Loader},
+};
+
+/// Configures which schemas are generated and where the generated module is written to
+#[derive(Clone, Debug)]
+pub struct Config {
+    inputs: Vec<String>,
+    files: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    module: String,
+    derives: Option<Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            files: Vec::new(),
+            out_dir: None,
+            module: "types".to_owned(),
+            derives: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a schema or a glob pattern of schemas, where `*` and `?` match within a directory and `**` matches any directories
+    pub fn input(mut self, pattern: impl Into<String>) -> Self {
+        self.inputs.push(pattern.into());
+        self
+    }
+
+    /// Adds a schema by its path, which is used as is instead of being matched as a glob pattern
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.files.push(path.into());
+        self
+    }
+
+    /// Writes the generated module into the given directory instead of `OUT_DIR`
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// The name of the generated file without its extension, which defaults to `types`
+    pub fn module(mut self, module: impl Into<String>) -> Self {
+        self.module = module.into();
+        self
+    }
+
+    /// Replaces the traits that are derived next to `serde::Serialize` and `serde::Deserialize`
+    pub fn derives(mut self, derives: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.derives = Some(derives.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Generates the module and prints the instructions for cargo, returning the path of the generated file
+    ///
+    /// Problems in the schemas are printed as cargo warnings, so that they are shown next to the failed build.
+    pub fn compile(self) -> Result<PathBuf, Error> {
+        let result = self.compile_to(&mut io::stdout());
+        if let Err(error) = &result {
+            for line in error.to_string().lines() {
+                println!("cargo:warning={line}");
+            }
+        }
+
+        result
+    }
+
+    fn compile_to(&self, cargo: &mut impl Write) -> Result<PathBuf, Error> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR").ok_or(Error::MissingOutDir)?.into(),
+        };
+
+        // Changes to the directories are tracked, so that new schemas matching the patterns are picked up
+        for pattern in &self.inputs {
+            writeln!(
+                cargo,
+                "cargo:rerun-if-changed={}",
+                base_dir(pattern).display()
+            )?;
+        }
+
+        let module = self.generate()?;
+        for file in &module.files {
+            writeln!(cargo, "cargo:rerun-if-changed={}", file.display())?;
+        }
+
+        let path = out_dir.join(format!("{}.rs", self.module));
+        fs::write(&path, module.code)?;
+
+        Ok(path)
+    }
+
+    /// Generates the module in memory without writing it
+    pub fn generate(&self) -> Result<Module, Error> {
+        let mut inputs = inputs::resolve(Path::new(""), &self.inputs)?;
+        for file in &self.files {
+            if !inputs.contains(file) {
+                inputs.push(file.clone());
+            }
+        }
+
+        let symbols = Loader::new().load_all(&inputs)?;
+        let diagnostics = checker::check(&symbols);
+        if !diagnostics.is_empty() {
+            return Err(Error::Check(diagnostics));
+        }
+
+        let mut generator = match &self.derives {
+            Some(derives) => RustTypeGenerator::new().with_derives(derives.clone()),
+            None => RustTypeGenerator::new(),
+        };
+        for declaration in symbols.declarations() {
+            generator.add_declaration(declaration);
+        }
+
+        let source = inputs
+            .iter()
+            .map(|input| input.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let header = generator.header(&source, &format!("{:016x}", symbols.hash()));
+
+        Ok(Module {
+            code: format!("{header}\n{}\n", generator.inline_module()),
+            files: symbols.files().to_vec(),
+        })
+    }
+}
+
+/// A generated Rust module containing the types of all schemas
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Module {
+    pub code: String,
+    /// The schema files that the module was generated from, including all imports
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Neither an output directory was configured nor is `OUT_DIR` set, which cargo sets for build scripts
+    MissingOutDir,
+    /// An input pattern is invalid or does not match any file
+    Input(InputError),
+    Io(io::Error),
+    Load(LoadError),
+    Check(Vec<Diagnostic>),
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<InputError> for Error {
+    fn from(error: InputError) -> Self {
+        Error::Input(error)
+    }
+}
+
+impl From<LoadError> for Error {
+    fn from(error: LoadError) -> Self {
+        Error::Load(error)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingOutDir => write!(
+                f,
+                "OUT_DIR is not set, which is only the case outside of build scripts"
+            ),
+            Error::Input(error) => write!(f, "{error}"),
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Load(error) => write!(f, "{error}"),
+            Error::Check(diagnostics) => {
+                let diagnostics = diagnostics
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                write!(f, "{}", diagnostics.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::PathBuf};
+
+    use super::Config;
+
+    #[test]
+    fn compile_fixture() {
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let out_dir = env::temp_dir().join(format!("type-lang-build-{}", std::process::id()));
+        fs::create_dir_all(&out_dir).unwrap();
+
+        let mut cargo = Vec::new();
+        let path = Config::new()
+            .input(fixtures.join("*.type").to_string_lossy())
+            .out_dir(&out_dir)
+            .compile_to(&mut cargo)
+            .unwrap();
+        let generated = fs::read_to_string(&path).unwrap();
+        let cargo = String::from_utf8(cargo).unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+
+        assert_eq!(path, out_dir.join("types.rs"));
+        assert!(generated.starts_with("// Generated by type-lang from "));
+        assert!(generated.contains("pub mod billing {\n\tmod status {\n"));
+        assert!(generated.contains("\tpub use invoice::Invoice;\n"));
+        assert!(cargo.contains(&format!("cargo:rerun-if-changed={}\n", fixtures.display())));
+        assert!(cargo.contains(&format!(
+            "cargo:rerun-if-changed={}\n",
+            fixtures.join("billing.type").display()
+        )));
+    }
+
+    #[test]
+    fn generate_files_with_glob_characters() {
+        let directory = env::temp_dir().join(format!("type-lang-[build]-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let schema = directory.join("user?.type");
+        fs::write(&schema, "type User { name: String }").unwrap();
+
+        let module = Config::new().file(&schema).generate();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let module = module.unwrap();
+        assert!(module.code.contains("pub struct User {"));
+        assert_eq!(module.files, vec![schema]);
+    }
+}
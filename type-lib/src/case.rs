@@ -0,0 +1,250 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A naming convention for identifiers and file names
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Case {
+    /// `snake_case`
+    Snake,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+    /// `kebab-case`
+    Kebab,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+}
+
+impl Case {
+    pub const ALL: [Case; 5] = [
+        Case::Snake,
+        Case::Camel,
+        Case::Pascal,
+        Case::Kebab,
+        Case::ScreamingSnake,
+    ];
+
+    /// Converts a name into this naming convention
+    pub fn convert(self, name: &str) -> String {
+        match self {
+            Case::Snake => to_snake_case(name),
+            Case::Camel => to_camel_case(name),
+            Case::Pascal => to_pascal_case(name),
+            Case::Kebab => to_kebab_case(name),
+            Case::ScreamingSnake => to_screaming_snake_case(name),
+        }
+    }
+
+    /// The name of the naming convention, written in the convention itself
+    pub fn name(self) -> &'static str {
+        match self {
+            Case::Snake => "snake_case",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+            Case::Kebab => "kebab-case",
+            Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+        }
+    }
+}
+
+impl Display for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Case {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Case::ALL
+            .into_iter()
+            .find(|case| case.name() == s)
+            .ok_or_else(|| {
+                let names = Case::ALL.map(Case::name).join(", ");
+                format!("Unknown naming convention '{s}', expected one of {names}")
+            })
+    }
+}
+
+/// Splits a name into its words
+///
+/// Words are separated by any non-alphanumeric character and by changes in capitalization.
+/// A run of capitals is treated as an acronym that ends before the last capital if a lowercase letter follows,
+/// so `HTTPServer` is split into `HTTP` and `Server`. Digits belong to the word they follow.
+pub fn split_words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let chars = part.char_indices().collect::<Vec<_>>();
+        let mut start = 0;
+
+        for (i, &(index, ch)) in chars.iter().enumerate().skip(1) {
+            let previous = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|&(_, c)| c);
+
+            let is_boundary = ch.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.is_some_and(char::is_lowercase)));
+
+            if is_boundary {
+                words.push(&part[start..index]);
+                start = index;
+            }
+        }
+
+        if start < part.len() {
+            words.push(&part[start..]);
+        }
+    }
+
+    words
+}
+
+/// The underscores that a name starts with, which are kept by conversions as they usually mark a name as private, e.g. `_id`
+fn leading_underscores(name: &str) -> &str {
+    &name[..name.len() - name.trim_start_matches('_').len()]
+}
+
+/// Uppercases the first character of a word and lowercases the rest
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+pub fn to_snake_case(name: &str) -> String {
+    let words = split_words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    format!("{}{words}", leading_underscores(name))
+}
+
+pub fn to_camel_case(name: &str) -> String {
+    let words = split_words(name)
+        .iter()
+        .enumerate()
+        .map(|(i, word)| match i {
+            0 => word.to_lowercase(),
+            _ => capitalize(word),
+        })
+        .collect::<String>();
+
+    format!("{}{words}", leading_underscores(name))
+}
+
+pub fn to_pascal_case(name: &str) -> String {
+    let words = split_words(name)
+        .iter()
+        .map(|word| capitalize(word))
+        .collect::<String>();
+
+    format!("{}{words}", leading_underscores(name))
+}
+
+pub fn to_kebab_case(name: &str) -> String {
+    let words = split_words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    format!("{}{words}", leading_underscores(name))
+}
+
+pub fn to_screaming_snake_case(name: &str) -> String {
+    let words = split_words(name)
+        .iter()
+        .map(|word| word.to_uppercase())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    format!("{}{words}", leading_underscores(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("ArmorKind"), vec!["Armor", "Kind"]);
+        assert_eq!(split_words("isAdmin"), vec!["is", "Admin"]);
+        assert_eq!(split_words("is_admin"), vec!["is", "admin"]);
+        assert_eq!(split_words("user-id"), vec!["user", "id"]);
+        assert_eq!(split_words("__private__"), vec!["private"]);
+        assert_eq!(split_words("_"), Vec::<&str>::new());
+        assert_eq!(split_words(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_split_acronyms() {
+        assert_eq!(split_words("HTTPServer"), vec!["HTTP", "Server"]);
+        assert_eq!(split_words("userID"), vec!["user", "ID"]);
+        assert_eq!(
+            split_words("parseJSONValue"),
+            vec!["parse", "JSON", "Value"]
+        );
+        assert_eq!(split_words("URL"), vec!["URL"]);
+    }
+
+    #[test]
+    fn test_split_digits() {
+        assert_eq!(split_words("Int64"), vec!["Int64"]);
+        assert_eq!(split_words("utf8String"), vec!["utf8", "String"]);
+        assert_eq!(split_words("Vec3D"), vec!["Vec3", "D"]);
+        assert_eq!(split_words("level_2"), vec!["level", "2"]);
+    }
+
+    #[test]
+    fn test_convert() {
+        let name = "HTTPResponseCode2";
+        assert_eq!(to_snake_case(name), "http_response_code2");
+        assert_eq!(to_camel_case(name), "httpResponseCode2");
+        assert_eq!(to_pascal_case(name), "HttpResponseCode2");
+        assert_eq!(to_kebab_case(name), "http-response-code2");
+        assert_eq!(to_screaming_snake_case(name), "HTTP_RESPONSE_CODE2");
+    }
+
+    #[test]
+    fn test_convert_keeps_leading_underscores() {
+        assert_eq!(to_snake_case("_id"), "_id");
+        assert_eq!(to_camel_case("__type_name"), "__typeName");
+        assert_eq!(to_pascal_case("_id_"), "_Id");
+        assert_eq!(to_snake_case("_"), "_");
+    }
+
+    #[test]
+    fn test_convert_is_idempotent() {
+        for case in Case::ALL {
+            for name in [
+                "armor_kind",
+                "ArmorKind",
+                "armorKind",
+                "ARMOR_KIND",
+                "armor-kind",
+                "_armor_kind",
+            ] {
+                let converted = case.convert(name);
+                assert_eq!(case.convert(&converted), converted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_case() {
+        for case in Case::ALL {
+            assert_eq!(case.name().parse(), Ok(case));
+        }
+        assert!("Title Case".parse::<Case>().is_err());
+    }
+}
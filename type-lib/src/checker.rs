@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    case::to_snake_case,
+    loader::SymbolTable,
+    parser::{Declaration, TypeItem},
+};
+
+/// A problem in a schema that is syntactically valid
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file of the declaration that the problem was found in
+    pub path: PathBuf,
+    /// The qualified name of the declaration
+    pub ident: String,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Checks that all declarations only refer to existing types and use them with the right number of type arguments
+pub fn check(symbols: &SymbolTable) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for declaration in symbols.declarations() {
+        let ident = declaration.qualified_ident();
+        let path = symbols.path(&ident).map(Path::to_owned).unwrap_or_default();
+        let mut report = |message: String| {
+            diagnostics.push(Diagnostic {
+                path: path.clone(),
+                ident: ident.clone(),
+                message,
+            })
+        };
+
+        let mut generics = HashSet::new();
+        for generic in declaration.generics() {
+            if !generics.insert(generic) {
+                report(format!(
+                    "Type parameter '{generic}' of '{ident}' is declared more than once"
+                ));
+            }
+        }
+
+        match declaration {
+            Declaration::Type(ty) => {
+                let mut fields = HashSet::new();
+                let mut names = HashMap::new();
+                let mut wire_names = HashSet::new();
+                for field in &ty.fields {
+                    let context = format!("field '{}' of '{ident}'", field.ident);
+                    // Generated code names fields in snake case, so fields like `userId` and `user_id` would be the same
+                    let name = to_snake_case(&field.ident);
+                    if !fields.insert(&field.ident) {
+                        report(format!("The {context} is declared more than once"));
+                    } else if let Some(other) = names.insert(name.clone(), &field.ident) {
+                        report(format!(
+                            "The {context} is named '{name}' in generated code like the field '{other}'"
+                        ));
+                    } else if !wire_names.insert(field.wire_name(ty.naming)) {
+                        report(format!(
+                            "The key '{}' of the {context} is already used by another field",
+                            field.wire_name(ty.naming)
+                        ));
+                    }
+
+                    check_item(&field.ty, declaration, symbols, &context, &mut report);
+                }
+            }
+            Declaration::Alias(alias) => {
+                let context = format!("alias '{ident}'");
+                check_item(&alias.ty, declaration, symbols, &context, &mut report);
+            }
+            Declaration::Newtype(newtype) => {
+                let context = format!("newtype '{ident}'");
+                check_item(&newtype.ty, declaration, symbols, &context, &mut report);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn check_item(
+    item: &TypeItem,
+    declaration: &Declaration,
+    symbols: &SymbolTable,
+    context: &str,
+    report: &mut impl FnMut(String),
+) {
+    match item {
+        TypeItem::Array(inner) | TypeItem::Optional(inner) => {
+            check_item(inner, declaration, symbols, context, report)
+        }
+        TypeItem::Dict { key, value } => {
+            if !is_valid_key(key, declaration, symbols) {
+                report(format!(
+                    "The key type '{key}' in the {context} must be a built-in type or a newtype of String"
+                ));
+            }
+            check_item(key, declaration, symbols, context, report);
+            check_item(value, declaration, symbols, context, report);
+        }
+        TypeItem::Basic(ident, arguments) => {
+            let is_parameter = declaration
+                .generics()
+                .iter()
+                .any(|param| **param == **ident);
+            let expected = if item.is_builtin() || is_parameter {
+                Some(0)
+            } else {
+                symbols
+                    .get(ident)
+                    .map(|declaration| declaration.generics().len())
+            };
+
+            match expected {
+                None => report(format!("Unknown type '{ident}' in the {context}")),
+                Some(expected) if expected != arguments.len() => report(format!(
+                    "Type '{ident}' in the {context} expects {expected} type arguments, but got {}",
+                    arguments.len()
+                )),
+                Some(_) => {}
+            }
+
+            for argument in arguments {
+                check_item(argument, declaration, symbols, context, report);
+            }
+        }
+    }
+}
+
+/// Keys of JSON objects are strings, so dict keys must be built-in types or newtypes that wrap a string
+fn is_valid_key(key: &TypeItem, declaration: &Declaration, symbols: &SymbolTable) -> bool {
+    let ident = match key {
+        TypeItem::Basic(ident, arguments) if arguments.is_empty() => ident,
+        _ => return false,
+    };
+    if key.is_builtin() {
+        return true;
+    }
+    if declaration
+        .generics()
+        .iter()
+        .any(|param| **param == **ident)
+    {
+        return false;
+    }
+
+    match symbols.get(ident) {
+        Some(Declaration::Newtype(newtype)) => {
+            matches!(&newtype.ty, TypeItem::Basic(target, arguments) if target == "String" && arguments.is_empty())
+        }
+        // Unknown types are already reported as such
+        None => true,
+        Some(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use crate::loader::Loader;
+
+    use super::*;
+
+    fn check_source(source: &'static str) -> Vec<String> {
+        let loader = Loader::with_reader(move |path| match path == Path::new("schema.type") {
+            true => Ok(source.to_owned()),
+            false => Err(io::ErrorKind::NotFound.into()),
+        });
+        let symbols = loader.load("schema.type").unwrap();
+
+        check(&symbols)
+            .into_iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn accept_valid_schema() {
+        let source = "type User { name: String, tags: [Tag], page: Page<Tag>? }
+            type Tag { label: String }
+            type Page<T> { items: [T] }
+            alias Tags = [Tag]
+            newtype Email = String
+            type Inbox { unread: {Email: Int}, counts: {Int: Bool} }";
+
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn report_unknown_types() {
+        let source = "type User { account: Account, tags: {String: [Tag]} }
+            alias Accounts = [Account]";
+
+        assert_eq!(
+            check_source(source),
+            vec![
+                "schema.type: Unknown type 'Account' in the field 'account' of 'User'",
+                "schema.type: Unknown type 'Tag' in the field 'tags' of 'User'",
+                "schema.type: Unknown type 'Account' in the alias 'Accounts'",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_generic_arity() {
+        let source = "type Page<T> { items: [T], first: T<String> }
+            type Feed { users: Page, pages: Page<Int, Int>, count: Int<Bool> }";
+
+        assert_eq!(
+            check_source(source),
+            vec![
+                "schema.type: Type 'T' in the field 'first' of 'Page' expects 0 type arguments, but got 1",
+                "schema.type: Type 'Page' in the field 'users' of 'Feed' expects 1 type arguments, but got 0",
+                "schema.type: Type 'Page' in the field 'pages' of 'Feed' expects 1 type arguments, but got 2",
+                "schema.type: Type 'Int' in the field 'count' of 'Feed' expects 0 type arguments, but got 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_duplicate_fields() {
+        let source = r#"type Pair<T, T> { a: Int, a: Int, @json("a") b: Int, userId: Int, user_id: Int, id_: Int, id: Int }"#;
+
+        assert_eq!(
+            check_source(source),
+            vec![
+                "schema.type: Type parameter 'T' of 'Pair' is declared more than once",
+                "schema.type: The field 'a' of 'Pair' is declared more than once",
+                "schema.type: The key 'a' of the field 'b' of 'Pair' is already used by another field",
+                "schema.type: The field 'user_id' of 'Pair' is named 'user_id' in generated code like the field 'userId'",
+                "schema.type: The field 'id' of 'Pair' is named 'id' in generated code like the field 'id_'",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_invalid_dict_keys() {
+        let source = "type Index<K> { byTag: {Tag: Int}, byParam: {K: Int}, byList: {[String]: Int}, byId: {Id: Int} }
+            type Tag { label: String }
+            newtype Id = Int";
+
+        assert_eq!(
+            check_source(source),
+            vec![
+                "schema.type: The key type 'Tag' in the field 'byTag' of 'Index' must be a built-in type or a newtype of String",
+                "schema.type: The key type 'K' in the field 'byParam' of 'Index' must be a built-in type or a newtype of String",
+                "schema.type: The key type '[String]' in the field 'byList' of 'Index' must be a built-in type or a newtype of String",
+                "schema.type: The key type 'Id' in the field 'byId' of 'Index' must be a built-in type or a newtype of String",
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    case::Case,
+    parser::{Declaration, Field, ParseError, Parser, Schema, Type},
+    tokenizer,
+};
+
+/// Formats the source of a schema, unless it has syntax errors or comments that formatting would remove
+pub fn format_source(source: &str) -> Result<String, FormatError> {
+    let schema = Parser::new(source)
+        .parse_schema()
+        .map_err(FormatError::Parse)?;
+    let formatted = format(&schema);
+
+    // Comments are only kept around declarations and fields, but not e.g. between the parts of a type
+    let mut kept = tokenizer::comments(&formatted)
+        .into_iter()
+        .map(|comment| comment.str)
+        .collect::<Vec<_>>();
+    for comment in tokenizer::comments(source) {
+        match kept.iter().position(|kept| *kept == comment.str) {
+            Some(index) => {
+                kept.swap_remove(index);
+            }
+            None => {
+                let line = source[..comment.span.start()].matches('\n').count() + 1;
+                return Err(FormatError::MisplacedComment { line });
+            }
+        }
+    }
+
+    Ok(formatted)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    Parse(ParseError),
+    /// A comment is written where formatting would remove it
+    MisplacedComment {
+        line: usize,
+    },
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Parse(error) => write!(f, "{error}"),
+            FormatError::MisplacedComment { line } => write!(
+                f,
+                "The comment at line {line} would be removed by formatting, move it above or behind a field or declaration"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Prints a schema in the canonical formatting of the type language
+pub fn format(schema: &Schema) -> String {
+    let mut sections = Vec::new();
+
+    if let Some(package) = &schema.package {
+        sections.push(format!("package {package}"));
+    }
+    if let Some(naming) = schema.naming {
+        sections.push(format!("naming {}", quote(naming.name())));
+    }
+    if !schema.imports.is_empty() {
+        let imports = schema
+            .imports
+            .iter()
+            .map(|path| format!("import {}", quote(path)))
+            .collect::<Vec<_>>();
+        sections.push(imports.join("\n"));
+    }
+    if let Some(first) = sections.first_mut() {
+        first.insert_str(0, &format_comments(&schema.comments, ""));
+    }
+
+    for declaration in &schema.declarations {
+        sections.push(format_in_file(declaration, schema.naming));
+    }
+    if !schema.end_comments.is_empty() {
+        let comments = format_comments(&schema.end_comments, "");
+        sections.push(comments.trim_end().to_owned());
+    }
+
+    if sections.is_empty() {
+        return String::new();
+    }
+
+    let mut formatted = sections.join("\n\n");
+    formatted.push('\n');
+    formatted
+}
+
+/// Prints a single declaration on its own, e.g. to show it outside of its schema
+pub fn format_declaration(declaration: &Declaration) -> String {
+    format_in_file(declaration, None)
+}
+
+fn format_in_file(declaration: &Declaration, naming: Option<Case>) -> String {
+    let comments = declaration.comments();
+    let mut formatted = format_comments(&comments.leading, "");
+    formatted.push_str(&match declaration {
+        Declaration::Type(ty) => format_type(ty, naming),
+        Declaration::Alias(alias) => format!("alias {} = {}", alias.ident, alias.ty),
+        Declaration::Newtype(newtype) => format!("newtype {} = {}", newtype.ident, newtype.ty),
+    });
+    formatted.push_str(&format_trailing(&comments.trailing));
+
+    formatted
+}
+
+fn format_type(ty: &Type, file_naming: Option<Case>) -> String {
+    let mut formatted = String::new();
+
+    // Types only need their own naming convention if it differs from the file-wide one
+    if let Some(naming) = ty.naming.filter(|naming| Some(*naming) != file_naming) {
+        formatted.push_str(&format!("@naming({})\n", quote(naming.name())));
+    }
+
+    formatted.push_str(&format!("type {}", ty.ident));
+    if !ty.generics.is_empty() {
+        formatted.push_str(&format!("<{}>", ty.generics.join(", ")));
+    }
+
+    if ty.fields.is_empty() && ty.end_comments.is_empty() {
+        formatted.push_str(" {}");
+    } else {
+        let fields = ty.fields.iter().map(format_field).collect::<String>();
+        let end_comments = format_comments(&ty.end_comments, "\t");
+        formatted.push_str(&format!(" {{\n{fields}{end_comments}}}"));
+    }
+
+    formatted
+}
+
+fn format_field(field: &Field) -> String {
+    let annotation = match &field.json {
+        Some(json) => format!("@json({}) ", quote(json)),
+        None => "".to_owned(),
+    };
+
+    format!(
+        "{}\t{annotation}{}: {}{}\n",
+        format_comments(&field.comments.leading, "\t"),
+        field.ident,
+        field.ty,
+        format_trailing(&field.comments.trailing)
+    )
+}
+
+/// Writes each comment on its own line, where empty comments are blank lines
+fn format_comments(comments: &[Box<str>], indent: &str) -> String {
+    comments
+        .iter()
+        .map(|comment| match comment.is_empty() {
+            true => "\n".to_owned(),
+            false => format!("{indent}{comment}\n"),
+        })
+        .collect()
+}
+
+fn format_trailing(comment: &Option<Box<str>>) -> String {
+    match comment {
+        Some(comment) => format!(" {comment}"),
+        None => String::new(),
+    }
+}
+
+/// Writes a string literal, escaping quotes and backslashes
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::Parser;
+
+    use super::{format, FormatError};
+
+    fn format_source(source: &str) -> String {
+        format(&Parser::new(source).parse_schema().unwrap())
+    }
+
+    #[test]
+    fn format_canonically() {
+        let source = r#"import "tag.type"   naming "camelCase"
+            package billing
+            type Invoice<T>{ items: [T], @json("@type") kind:String ,
+            total:   Double? }
+            @naming("camelCase") type Empty {
+            }
+            @naming("snake_case")
+            type Status { paid: Bool }
+            alias Invoices=[Invoice<Tag>]
+            newtype Email   = String"#;
+
+        assert_eq!(
+            format_source(source),
+            r#"package billing
+
+naming "camelCase"
+
+import "tag.type"
+
+type Invoice<T> {
+	items: [T]
+	@json("@type") kind: String
+	total: Double?
+}
+
+type Empty {}
+
+@naming("snake_case")
+type Status {
+	paid: Bool
+}
+
+alias Invoices = [Invoice<Tag>]
+
+newtype Email = String
+"#
+        );
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        let source = r#"type User { name: String, tags: {String: [Tag?]}, @json("say \"hi\"") greeting: String }"#;
+        let formatted = format_source(source);
+
+        assert_eq!(format_source(&formatted), formatted);
+        assert!(formatted.contains(r#"@json("say \"hi\"") greeting: String"#));
+    }
+
+    #[test]
+    fn format_empty_schema() {
+        assert_eq!(format_source(""), "");
+    }
+
+    #[test]
+    fn keep_comments() {
+        let source = r#"// Types of the shop
+import "tag.type" // for tags
+package shop
+// A user
+/// of the shop
+type User { // identity
+  name: String, // the full name
+
+  // Optional fields
+    nickname: String?
+  // TODO: more fields
+}
+alias Tags = [Tag] // sorted
+type Empty {
+// nothing yet
+}
+
+// The end
+
+// Really"#;
+        let formatted = super::format_source(source).unwrap();
+
+        assert_eq!(
+            formatted,
+            r#"// Types of the shop
+// for tags
+package shop
+
+import "tag.type"
+
+// A user
+/// of the shop
+type User {
+	// identity
+	name: String // the full name
+	// Optional fields
+	nickname: String?
+	// TODO: more fields
+}
+
+alias Tags = [Tag] // sorted
+
+type Empty {
+	// nothing yet
+}
+
+// The end
+
+// Really
+"#
+        );
+        assert_eq!(super::format_source(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn reject_misplaced_comments() {
+        let source = "type User {}\nalias Users // all of them\n= [User]";
+
+        assert_eq!(
+            super::format_source(source),
+            Err(FormatError::MisplacedComment { line: 2 })
+        );
+    }
+}
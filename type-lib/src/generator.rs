@@ -1,10 +1,16 @@
 use std::borrow::Cow;
 
-use crate::parser::{Field, Type, TypeItem};
+use crate::parser::{Alias, Declaration, Field, Newtype, Type, TypeItem};
 
+pub mod diagram;
 pub mod gleam;
 pub mod rust;
 
+/// Marks the first line of the header of generated files
+const HEADER_MARKER: &str = "Generated by type-lang";
+/// Precedes the schema hash in the second line of the header of generated files
+const HASH_LABEL: &str = "schema-hash:";
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct OutputFile {
     pub name: String,
@@ -12,24 +18,28 @@ pub struct OutputFile {
 }
 
 pub trait Generator {
+    /// Generates any top-level declaration and adds it to the internal state
+    fn add_declaration(&mut self, declaration: &Declaration) {
+        self.begin_package(declaration.package());
+
+        match declaration {
+            Declaration::Type(ty) => self.add_type(ty),
+            Declaration::Alias(alias) => self.add_alias(alias),
+            Declaration::Newtype(newtype) => self.add_newtype(newtype),
+        }
+    }
+
     /// Generates a type declaration and adds it to the internal state
     fn add_type(&mut self, ty: &Type) {
+        self.begin_type(ty);
+
         let fields = self.generate_fields(ty);
 
         let decoder = self.create_decoder(ty);
+        let encoder = self.create_encoder(ty);
 
         let declaration = self.generate_declaration(&ty.ident, &fields);
-        let content = format!(
-            "{}\n\n{declaration}\n\n{decoder}\n",
-            self.generate_imports(),
-        )
-        .trim()
-        .to_owned();
-
-        let file = OutputFile {
-            name: self.to_file_name(&ty.ident),
-            content,
-        };
+        let file = self.create_file(&ty.ident, &[declaration, decoder, encoder]);
         self.add_type_boilerplate(ty, &file);
 
         self.push_type(file);
@@ -37,14 +47,47 @@ pub trait Generator {
         self.reset();
     }
 
+    /// Generates a type alias and adds it to the internal state
+    fn add_alias(&mut self, alias: &Alias);
+
+    /// Generates a newtype wrapping another type and adds it to the internal state
+    fn add_newtype(&mut self, newtype: &Newtype);
+
+    /// Assembles the file for a declaration from the imports and the given code parts, skipping empty parts
+    fn create_file(&self, ident: &str, parts: &[String]) -> OutputFile {
+        let imports = self.generate_imports();
+        let content = std::iter::once(&imports)
+            .chain(parts)
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        OutputFile {
+            name: self.to_file_name(ident),
+            content,
+        }
+    }
+
+    /// An optional hook to prepare the internal state for the package of the next declaration
+    fn begin_package(&mut self, _package: Option<&str>) {}
+
+    /// An optional hook to prepare the internal state before the type is generated
+    fn begin_type(&mut self, _ty: &Type) {}
+
     /// An optional hook to add boilerplate after the type has been created
     fn add_type_boilerplate(&mut self, _ty: &Type, _file: &OutputFile) {}
 
-    /// Create encoder code. This is not needed for languages with decorator-based serialization.
+    /// Create decoder code. This is not needed for languages with decorator-based serialization.
     fn create_decoder(&mut self, _ty: &Type) -> String {
         "".to_owned()
     }
 
+    /// Create encoder code. This is not needed for languages with decorator-based serialization.
+    fn create_encoder(&mut self, _ty: &Type) -> String {
+        "".to_owned()
+    }
+
     /// The separator between struct members in the target language    
     fn field_separator(&self) -> &'static str;
 
@@ -74,7 +117,18 @@ pub trait Generator {
     /// Generate a type annotation
     fn generate_type_item(&mut self, ty: &TypeItem) -> String;
 
-    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &OutputFile> + 'a>;
+    /// A comment that warns against editing a generated file, stamped with the hash of the schema it was generated from
+    fn header(&self, source: &str, hash: &str) -> String {
+        let comment = self.line_comment();
+        format!("{comment} {HEADER_MARKER} from {source}, do not edit.\n{comment} {HASH_LABEL} {hash}\n")
+    }
+
+    /// The token that starts a comment until the end of the line
+    fn line_comment(&self) -> &'static str {
+        "//"
+    }
+
+    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &'a OutputFile> + 'a>;
 
     /// Resets the builder between types, e.g. resets flags
     fn reset(&mut self) {}
@@ -88,3 +142,109 @@ pub trait Generator {
     /// Add the type file to the generated output
     fn push_type(&mut self, ty: OutputFile);
 }
+
+/// Splits a generated file into the schema hash of its header and the content after the header
+pub fn split_header(file: &str) -> Option<(&str, &str)> {
+    let (first, rest) = file.split_once('\n')?;
+    let (second, content) = rest.split_once('\n')?;
+    if !first.contains(HEADER_MARKER) {
+        return None;
+    }
+
+    let (_, hash) = second.split_once(HASH_LABEL)?;
+    Some((hash.trim(), content.strip_prefix('\n').unwrap_or(content)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::Parser;
+
+    use super::{
+        diagram::{DiagramFormat, DiagramGenerator},
+        gleam::{DecodeVersion, GleamTypeGenerator},
+        rust::RustTypeGenerator,
+        split_header, Generator,
+    };
+
+    const SCHEMA: &str = "type User {
+        name: String
+        nickname: String?
+        account: Account
+        tags: [Tag]
+        armor: {ArmorKind: Item}
+        scores: {Int: Double}
+        friends: [User]
+    }
+    type Account { id: UInt64, owner: User?, items: [Item] }
+    type Tag { label: String }
+    alias Tags = [Tag]
+    newtype Email = String";
+
+    fn render(mut generator: Box<dyn Generator>) -> Vec<(String, String)> {
+        for declaration in Parser::new(SCHEMA).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        generator
+            .output_dyn()
+            .map(|file| (file.name.clone(), file.content.clone()))
+            .collect()
+    }
+
+    fn assert_deterministic(create: impl Fn() -> Box<dyn Generator>) {
+        let first = render(create());
+        for _ in 0..10 {
+            assert_eq!(render(create()), first);
+        }
+    }
+
+    #[test]
+    fn gleam_output_is_deterministic() {
+        assert_deterministic(|| GleamTypeGenerator::boxed());
+        assert_deterministic(|| {
+            Box::new(GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib))
+        });
+    }
+
+    #[test]
+    fn rust_output_is_deterministic() {
+        assert_deterministic(|| RustTypeGenerator::boxed());
+    }
+
+    #[test]
+    fn diagram_output_is_deterministic() {
+        assert_deterministic(|| Box::new(DiagramGenerator::new(DiagramFormat::Mermaid)));
+        assert_deterministic(|| Box::new(DiagramGenerator::new(DiagramFormat::Graphviz)));
+    }
+
+    #[test]
+    fn gleam_imports_are_sorted() {
+        let files = render(GleamTypeGenerator::boxed());
+        let imports = files[0]
+            .1
+            .lines()
+            .take_while(|line| line.starts_with("import "))
+            .collect::<Vec<_>>();
+
+        let mut sorted = imports.clone();
+        sorted.sort();
+        assert_eq!(imports, sorted);
+    }
+
+    #[test]
+    fn split_generated_header() {
+        let mermaid = DiagramGenerator::new(DiagramFormat::Mermaid);
+        let header = mermaid.header("user.type", "0123456789abcdef");
+        assert_eq!(
+            header,
+            "%% Generated by type-lang from user.type, do not edit.\n%% schema-hash: 0123456789abcdef\n"
+        );
+
+        let file = format!("{header}\nclassDiagram\n");
+        assert_eq!(
+            split_header(&file),
+            Some(("0123456789abcdef", "classDiagram\n"))
+        );
+        assert_eq!(split_header("classDiagram\n"), None);
+    }
+}
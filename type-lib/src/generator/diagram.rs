@@ -0,0 +1,475 @@
+use std::{borrow::Cow, cell::OnceCell, collections::HashSet};
+
+use crate::parser::{qualify, Alias, Field, Newtype, Type, TypeItem};
+
+use super::{Generator, OutputFile};
+
+/// The diagram language that the schema overview is rendered to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// A Mermaid class diagram
+    #[default]
+    Mermaid,
+    /// A Graphviz DOT digraph
+    Graphviz,
+}
+
+/// A reference from the node of a declaration to the node of another type
+struct Edge {
+    source: String,
+    /// The package of the declaration that the reference is written in
+    package: Option<String>,
+    /// The referenced type as it is written in the schema
+    target: String,
+    label: String,
+    cardinality: &'static str,
+}
+
+/// Renders all types into a single diagram, where nodes are types and edges are field references
+pub struct DiagramGenerator {
+    format: DiagramFormat,
+    /// The package of the current declaration, which qualifies its node
+    package: Option<String>,
+    /// The type parameters of the current type, which are not rendered as references
+    generics: Vec<Box<str>>,
+    nodes: Vec<String>,
+    edges: Vec<Edge>,
+    /// The qualified names of all declarations, which unqualified references are resolved against
+    declared: HashSet<String>,
+    /// The diagram is rendered once all declarations are added, on the first request of the output
+    diagram: OnceCell<OutputFile>,
+}
+
+impl Generator for DiagramGenerator {
+    fn begin_package(&mut self, package: Option<&str>) {
+        self.package = package.map(str::to_owned);
+    }
+
+    fn begin_type(&mut self, ty: &Type) {
+        self.generics = ty.generics.clone();
+    }
+
+    fn add_type_boilerplate(&mut self, ty: &Type, _file: &OutputFile) {
+        self.declared
+            .insert(qualify(self.package.as_deref(), &ty.ident));
+        let source = self.node_id(&ty.ident);
+        for field in &ty.fields {
+            self.add_edges(&source, &field.ident, &field.ty);
+        }
+    }
+
+    fn add_alias(&mut self, alias: &Alias) {
+        self.add_wrapper(&alias.ident, "alias", &alias.ty);
+    }
+
+    fn add_newtype(&mut self, newtype: &Newtype) {
+        self.add_wrapper(&newtype.ident, "newtype", &newtype.ty);
+    }
+
+    fn field_separator(&self) -> &'static str {
+        match self.format {
+            DiagramFormat::Mermaid => "\n",
+            DiagramFormat::Graphviz => "\\l",
+        }
+    }
+
+    fn file_extension(&self) -> &'static str {
+        match self.format {
+            DiagramFormat::Mermaid => "mmd",
+            DiagramFormat::Graphviz => "dot",
+        }
+    }
+
+    fn line_comment(&self) -> &'static str {
+        match self.format {
+            DiagramFormat::Mermaid => "%%",
+            DiagramFormat::Graphviz => "//",
+        }
+    }
+
+    fn generate(self) -> Vec<OutputFile> {
+        vec![self.render()]
+    }
+
+    fn generate_declaration(&self, ident: &str, fields: &str) -> String {
+        let id = self.node_id(ident);
+        let generics = self.generics.join(", ");
+        match self.format {
+            DiagramFormat::Mermaid if generics.is_empty() => {
+                format!("class {id} {{\n{fields}\n\t}}")
+            }
+            DiagramFormat::Mermaid => format!("class {id}~{generics}~ {{\n{fields}\n\t}}"),
+            DiagramFormat::Graphviz => {
+                let ident = qualify(self.package.as_deref(), ident);
+                let title = match generics.is_empty() {
+                    true => ident,
+                    false => escape_record_label(&format!("{ident}<{generics}>")),
+                };
+                match fields.is_empty() {
+                    true => format!("{id} [label=\"{{{title}}}\"];"),
+                    false => format!("{id} [label=\"{{{title}|{fields}\\l}}\"];"),
+                }
+            }
+        }
+    }
+
+    fn generate_field(&mut self, field: &Field) -> String {
+        let ty = self.generate_type_item(&field.ty);
+        match self.format {
+            DiagramFormat::Mermaid => format!("\t\t+{ty} {}", field.ident),
+            DiagramFormat::Graphviz => format!("{}: {ty}", field.ident),
+        }
+    }
+
+    fn generate_type_item(&mut self, ty: &TypeItem) -> String {
+        match self.format {
+            // Mermaid uses a tilde notation for generics and would end the class body on a closing brace
+            DiagramFormat::Mermaid => match ty {
+                TypeItem::Array(element) => format!("List~{}~", self.generate_type_item(element)),
+                TypeItem::Dict { key, value } => format!(
+                    "Dict~{}, {}~",
+                    self.generate_type_item(key),
+                    self.generate_type_item(value)
+                ),
+                TypeItem::Optional(inner) => format!("{}?", self.generate_type_item(inner)),
+                TypeItem::Basic(ident, arguments) if arguments.is_empty() => ident.clone(),
+                TypeItem::Basic(ident, arguments) => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|argument| self.generate_type_item(argument))
+                        .collect::<Vec<_>>();
+                    format!("{ident}~{}~", arguments.join(", "))
+                }
+            },
+            DiagramFormat::Graphviz => escape_record_label(&ty.to_string()),
+        }
+    }
+
+    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &'a OutputFile> + 'a> {
+        Box::new(std::iter::once(self.diagram.get_or_init(|| self.render())))
+    }
+
+    fn sanitize_ident<'a>(&self, ident: &'a str) -> Cow<'a, str> {
+        ident.into()
+    }
+
+    fn to_file_name(&self, name: &str) -> String {
+        name.to_owned()
+    }
+
+    fn push_type(&mut self, ty: OutputFile) {
+        self.nodes.push(format!("\t{}", ty.content));
+        // Adding a declaration after the output was requested renders the diagram again
+        self.diagram.take();
+    }
+}
+
+impl Default for DiagramGenerator {
+    fn default() -> Self {
+        Self::new(DiagramFormat::default())
+    }
+}
+
+impl DiagramGenerator {
+    pub fn new(format: DiagramFormat) -> Self {
+        Self {
+            format,
+            package: None,
+            generics: Vec::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            declared: HashSet::new(),
+            diagram: OnceCell::new(),
+        }
+    }
+
+    pub fn boxed() -> Box<Self> {
+        Box::default()
+    }
+
+    /// Adds an edge from the source type to every declared type that is referenced by the type item
+    fn add_edges(&mut self, source: &str, name: &str, item: &TypeItem) {
+        let mut references = Vec::new();
+        collect_references(item, &self.generics, None, &mut references);
+
+        for (target, role) in references {
+            let label = match role {
+                Some(role) => format!("{name} ({role})"),
+                None => name.to_owned(),
+            };
+            self.edges.push(Edge {
+                source: source.to_owned(),
+                package: self.package.clone(),
+                target,
+                label,
+                cardinality: cardinality(item),
+            });
+        }
+    }
+
+    /// The node that an edge points to, which is in the package of the edge if the target is declared there
+    fn target_id(&self, edge: &Edge) -> String {
+        let qualified = match edge.target.contains('.') {
+            true => edge.target.clone(),
+            false => qualify(edge.package.as_deref(), &edge.target),
+        };
+
+        // References resolved by the loader may also name a type without a package from within a package
+        match self.declared.contains(&qualified) || !self.declared.contains(&edge.target) {
+            true => qualified.replace('.', "_"),
+            false => edge.target.replace('.', "_"),
+        }
+    }
+
+    fn render_edge(&self, edge: &Edge) -> String {
+        let Edge {
+            source,
+            label,
+            cardinality,
+            ..
+        } = edge;
+        let target = self.target_id(edge);
+
+        match self.format {
+            DiagramFormat::Mermaid => {
+                format!("\t{source} --> \"{cardinality}\" {target} : {label}")
+            }
+            DiagramFormat::Graphviz => {
+                format!("\t{source} -> {target} [label=\"{label} [{cardinality}]\"];")
+            }
+        }
+    }
+
+    /// Adds a node for an alias or newtype, annotated with its kind and showing the wrapped type
+    fn add_wrapper(&mut self, ident: &str, kind: &str, ty: &TypeItem) {
+        self.generics.clear();
+        self.declared
+            .insert(qualify(self.package.as_deref(), ident));
+        let id = self.node_id(ident);
+        let wrapped = self.generate_type_item(ty);
+        let node = match self.format {
+            DiagramFormat::Mermaid => {
+                format!("class {id} {{\n\t\t<<{kind}>>\n\t\t{wrapped}\n\t}}")
+            }
+            DiagramFormat::Graphviz => format!(
+                "{id} [label=\"{{{}|{wrapped}\\l}}\"];",
+                escape_record_label(&format!(
+                    "<<{kind}>> {}",
+                    qualify(self.package.as_deref(), ident)
+                ))
+            ),
+        };
+
+        self.add_edges(&id, kind, ty);
+        self.push_type(OutputFile {
+            name: ident.to_owned(),
+            content: node,
+        });
+    }
+
+    /// The identifier of the node of a declaration in the current package, which cannot contain dots
+    fn node_id(&self, ident: &str) -> String {
+        qualify(self.package.as_deref(), ident).replace('.', "_")
+    }
+
+    /// Renders the complete diagram from the nodes and edges collected so far
+    fn render(&self) -> OutputFile {
+        let edges = self.edges.iter().map(|edge| self.render_edge(edge));
+        let body = self
+            .nodes
+            .iter()
+            .cloned()
+            .chain(edges)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let content = match self.format {
+            DiagramFormat::Mermaid => format!("classDiagram\n{body}").trim().to_owned(),
+            DiagramFormat::Graphviz => {
+                format!("digraph schema {{\n\tnode [shape=record];\n{body}\n}}")
+            }
+        };
+
+        OutputFile {
+            name: "schema".to_owned(),
+            content,
+        }
+    }
+}
+
+/// Collects all declared types that are referenced by a type item, together with their role in a dict
+fn collect_references(
+    item: &TypeItem,
+    generics: &[Box<str>],
+    role: Option<&'static str>,
+    references: &mut Vec<(String, Option<&'static str>)>,
+) {
+    match item {
+        TypeItem::Array(element) => collect_references(element, generics, role, references),
+        TypeItem::Dict { key, value } => {
+            collect_references(key, generics, Some("key"), references);
+            collect_references(value, generics, Some("value"), references);
+        }
+        TypeItem::Optional(inner) => collect_references(inner, generics, role, references),
+        TypeItem::Basic(ident, arguments) => {
+            let is_parameter = generics.iter().any(|param| **param == *ident);
+            if !item.is_builtin() && !is_parameter {
+                references.push((ident.clone(), role))
+            }
+            for argument in arguments {
+                collect_references(argument, generics, role, references);
+            }
+        }
+    }
+}
+
+/// Returns the UML multiplicity of the types referenced by a field
+fn cardinality(item: &TypeItem) -> &'static str {
+    fn is_many(item: &TypeItem) -> bool {
+        match item {
+            TypeItem::Array(_) | TypeItem::Dict { .. } => true,
+            TypeItem::Optional(inner) => is_many(inner),
+            TypeItem::Basic(..) => false,
+        }
+    }
+
+    match item {
+        _ if is_many(item) => "*",
+        TypeItem::Optional(_) => "0..1",
+        _ => "1",
+    }
+}
+
+/// Escapes characters that have a special meaning in Graphviz record labels
+fn escape_record_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for ch in label.chars() {
+        if matches!(ch, '{' | '}' | '|' | '<' | '>' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        generator::{diagram::DiagramFormat, Generator},
+        parser::Parser,
+    };
+
+    use super::DiagramGenerator;
+
+    const SOURCE: &str = "type User {
+        name: String
+        account: Account?
+        tags: [Tag]
+        armor: {ArmorKind: Item}
+    }";
+
+    #[test]
+    fn convert_mermaid() {
+        let mut generator = DiagramGenerator::new(DiagramFormat::Mermaid);
+        for declaration in Parser::new(SOURCE).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            generator.generate()[0].content,
+            "classDiagram\n\tclass User {\n\t\t+String name\n\t\t+Account? account\n\t\t+List~Tag~ tags\n\t\t+Dict~ArmorKind, Item~ armor\n\t}\n\tUser --> \"0..1\" Account : account\n\tUser --> \"*\" Tag : tags\n\tUser --> \"*\" ArmorKind : armor (key)\n\tUser --> \"*\" Item : armor (value)"
+        );
+    }
+
+    #[test]
+    fn convert_graphviz() {
+        let mut generator = DiagramGenerator::new(DiagramFormat::Graphviz);
+        for declaration in Parser::new(SOURCE).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            generator.generate()[0].content,
+            "digraph schema {\n\tnode [shape=record];\n\tUser [label=\"{User|name: String\\laccount: Account?\\ltags: [Tag]\\larmor: \\{ArmorKind: Item\\}\\l}\"];\n\tUser -> Account [label=\"account [0..1]\"];\n\tUser -> Tag [label=\"tags [*]\"];\n\tUser -> ArmorKind [label=\"armor (key) [*]\"];\n\tUser -> Item [label=\"armor (value) [*]\"];\n}"
+        );
+    }
+
+    #[test]
+    fn convert_generics() {
+        let source = "type Page<T> { items: [T] } type Feed { users: Page<User> }";
+        let mut generator = DiagramGenerator::new(DiagramFormat::Mermaid);
+        for declaration in Parser::new(source).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            generator.generate()[0].content,
+            "classDiagram\n\tclass Page~T~ {\n\t\t+List~T~ items\n\t}\n\tclass Feed {\n\t\t+Page~User~ users\n\t}\n\tFeed --> \"1\" Page : users\n\tFeed --> \"1\" User : users"
+        );
+    }
+
+    #[test]
+    fn convert_alias_and_newtype() {
+        let source = "alias Friends = [User]\nnewtype Email = String";
+        let mut generator = DiagramGenerator::new(DiagramFormat::Mermaid);
+        for declaration in Parser::new(source).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            generator.generate()[0].content,
+            "classDiagram\n\tclass Friends {\n\t\t<<alias>>\n\t\tList~User~\n\t}\n\tclass Email {\n\t\t<<newtype>>\n\t\tString\n\t}\n\tFriends --> \"*\" User : alias"
+        );
+    }
+
+    #[test]
+    fn convert_packages() {
+        let source = "package billing\ntype Invoice { status: shipping.Status }";
+        let mut generator = DiagramGenerator::new(DiagramFormat::Graphviz);
+        for declaration in Parser::new(source).parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            generator.generate()[0].content,
+            "digraph schema {\n\tnode [shape=record];\n\tbilling_Invoice [label=\"{billing.Invoice|status: shipping.Status\\l}\"];\n\tbilling_Invoice -> shipping_Status [label=\"status [1]\"];\n}"
+        );
+    }
+
+    #[test]
+    fn resolve_unqualified_references_in_packages() {
+        let mut generator = DiagramGenerator::new(DiagramFormat::Mermaid);
+        let sources = [
+            "type User { name: String }",
+            "package billing\ntype Invoice { status: Status, user: User }\ntype Status { paid: Bool }",
+        ];
+        for source in sources {
+            for declaration in Parser::new(source).parse() {
+                generator.add_declaration(&declaration);
+            }
+        }
+
+        let content = &generator.generate()[0].content;
+        assert!(content.contains("\tbilling_Invoice --> \"1\" billing_Status : status\n"));
+        assert!(content.ends_with("\tbilling_Invoice --> \"1\" User : user"));
+    }
+
+    #[test]
+    fn render_declarations_added_after_output() {
+        let mut generator = DiagramGenerator::new(DiagramFormat::Mermaid);
+        for declaration in Parser::new("type User { name: String }").parse() {
+            generator.add_declaration(&declaration);
+        }
+        let first = generator.output_dyn().next().unwrap().content.clone();
+        for declaration in Parser::new("type Tag { label: String }").parse() {
+            generator.add_declaration(&declaration);
+        }
+
+        assert_eq!(first, "classDiagram\n\tclass User {\n\t\t+String name\n\t}");
+        assert_eq!(
+            generator.output_dyn().next().unwrap().content,
+            "classDiagram\n\tclass User {\n\t\t+String name\n\t}\n\tclass Tag {\n\t\t+String label\n\t}"
+        );
+    }
+}
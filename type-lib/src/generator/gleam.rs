@@ -1,45 +1,180 @@
-use std::{borrow::Cow, collections::HashSet};
+use std::{borrow::Cow, collections::BTreeSet};
 
-use crate::parser::{Field, Type, TypeItem};
+use crate::{
+    case::{to_snake_case, Case},
+    parser::{qualify, split_qualified, Alias, Field, Newtype, Type, TypeItem},
+};
 
 use super::{Generator, OutputFile};
 
-pub struct GleamTypeGenerator {
-    types: Vec<OutputFile>,
+/// Reserved words that cannot be used as labels, variables or module names
+const KEYWORDS: &[&str] = &[
+    "as",
+    "assert",
+    "auto",
+    "case",
+    "const",
+    "delegate",
+    "derive",
+    "echo",
+    "else",
+    "fn",
+    "if",
+    "implement",
+    "import",
+    "let",
+    "macro",
+    "opaque",
+    "panic",
+    "pub",
+    "test",
+    "todo",
+    "type",
+    "use",
+];
+
+/// Modules that generated modules may import, which must not be shadowed by variables
+const IMPORTED_MODULES: &[&str] = &[
+    "bool", "decode", "dict", "dynamic", "float", "int", "json", "option",
+];
+
+/// The decoding API that generated decoders are written against
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeVersion {
+    /// The deprecated `decode` package, using `decode.into` and `decode.parameter`
+    #[default]
+    Legacy,
+    /// The `gleam/dynamic/decode` module of the standard library, using `decode.field` and `decode.success`
+    Stdlib,
+}
 
+/// State of the module that is currently generated, which is discarded after each type
+#[derive(Default)]
+struct ModuleState {
+    /// The type that the module is generated for
+    ident: String,
+    /// The package of the type
+    package: Option<String>,
+    /// The wire naming convention of the type
+    naming: Option<Case>,
+    /// The type parameters of the type
+    generics: Vec<Box<str>>,
     needs_option: bool,
     needs_dict: bool,
-    /// Generated types that are used by fields of the current type
-    used_types: HashSet<String>,
+    /// Standard library modules that are needed to convert dict keys to strings when encoding
+    key_modules: BTreeSet<&'static str>,
+    /// Other generated types that are used by fields of the current type, qualified with their package
+    used_types: BTreeSet<String>,
+}
 
-    /// The module folder name that the generated types should be located in
+pub struct GleamTypeGenerator {
+    types: Vec<OutputFile>,
+    module: ModuleState,
+
+    /// The module folder that generated types without a package are located in, packages use their own folder
     module_name: String,
+    decode_version: DecodeVersion,
 }
 
 impl Generator for GleamTypeGenerator {
+    fn begin_package(&mut self, package: Option<&str>) {
+        self.module.package = package.map(str::to_owned);
+    }
+
+    fn begin_type(&mut self, ty: &Type) {
+        self.module.ident = ty.ident.to_string();
+        self.module.naming = ty.naming;
+        self.module.generics = ty.generics.clone();
+    }
+
+    fn add_alias(&mut self, alias: &Alias) {
+        self.module.ident = alias.ident.to_string();
+        let target = self.generate_type_item(&alias.ty);
+        let declaration = format!("pub type {} = {target}", alias.ident);
+
+        let decoder = self.decoder_functions(&format!("\t{}", self.type_item_decoder(&alias.ty)));
+
+        let param = self.variable_name(self.to_file_name(&alias.ident));
+        let encoder = format!(
+            "pub fn to_json({param}: {}) -> Json {{\n\t{}\n}}",
+            alias.ident,
+            self.type_item_encode(&alias.ty, &param)
+        );
+
+        let file = self.create_file(&alias.ident, &[declaration, decoder, encoder]);
+        self.push_type(file);
+        self.reset();
+    }
+
+    fn add_newtype(&mut self, newtype: &Newtype) {
+        let ident = &newtype.ident;
+        self.module.ident = ident.to_string();
+
+        // The constructor is hidden, so the wrapped value can only be accessed through the module
+        let target = self.generate_type_item(&newtype.ty);
+        let param = self.variable_name(self.to_file_name(ident));
+        let declaration = format!(
+            "pub opaque type {ident} {{\n\t{ident}({target})\n}}\n\npub fn new(value: {target}) -> {ident} {{\n\t{ident}(value)\n}}\n\npub fn value({param}: {ident}) -> {target} {{\n\tlet {ident}(value) = {param}\n\tvalue\n}}"
+        );
+
+        let decoder = self.decoder_functions(&format!(
+            "\t{} |> decode.map({ident})",
+            self.type_item_decoder(&newtype.ty)
+        ));
+
+        let encoder = format!(
+            "pub fn to_json({param}: {ident}) -> Json {{\n\tlet {ident}(value) = {param}\n\t{}\n}}",
+            self.type_item_encode(&newtype.ty, "value")
+        );
+
+        let file = self.create_file(ident, &[declaration, decoder, encoder]);
+        self.push_type(file);
+        self.reset();
+    }
+
     fn create_decoder(&mut self, ty: &Type) -> String {
-        let mut use_statements = Vec::new();
-        let mut constructor_params = Vec::new();
-        let mut field_decoders = Vec::new();
+        match self.decode_version {
+            DecodeVersion::Legacy => self.create_legacy_decoder(ty),
+            DecodeVersion::Stdlib => self.create_stdlib_decoder(ty),
+        }
+    }
 
-        for field in &ty.fields {
-            let decode_type = self.type_item_decoder(&field.ty);
-            use_statements.push(format!("use {} <- decode.parameter", field.ident));
-            constructor_params.push(field.ident.clone());
-            field_decoders.push(format!(
-                "|> decode.field(\"{}\", {decode_type})",
-                field.ident
-            ));
+    fn create_encoder(&mut self, ty: &Type) -> String {
+        let param = self.variable_name(self.to_file_name(&ty.ident));
+        // Generic types take an encoder for each type parameter
+        let encoder_params = self
+            .module
+            .generics
+            .iter()
+            .map(|generic| {
+                let generic = type_parameter(generic);
+                format!(", {generic}_to_json: fn({generic}) -> Json")
+            })
+            .collect::<String>();
+
+        if ty.fields.is_empty() {
+            return format!(
+                "pub fn to_json(_{param}: {}{}) -> Json {{\n\tjson.object([])\n}}",
+                self.type_head(),
+                encoder_params.replace(", ", ", _")
+            );
         }
 
-        let use_statements = use_statements.join("\n\t\t");
-        let constructor_params = constructor_params.join(", ");
-        let field_decoders = field_decoders.join("\n\t");
+        let fields = ty
+            .fields
+            .iter()
+            .map(|field| {
+                let value =
+                    self.type_item_encode(&field.ty, &format!("{param}.{}", field_name(field)));
+                let key = string_literal(&field.wire_name(ty.naming));
+                format!("\t\t#({key}, {value}),\n")
+            })
+            .collect::<String>();
 
         format!(
-        "pub fn decode(data: Dynamic) {{\n\tlet decoder = decode.into({{\n\t\t{use_statements}\n\n\t\t{}({constructor_params})\n\t}})\n\t{field_decoders}\n\n\tdecoder |> decode.from(data)\n}}",
-        ty.ident,
-    )
+            "pub fn to_json({param}: {}{encoder_params}) -> Json {{\n\tjson.object([\n{fields}\t])\n}}",
+            self.type_head()
+        )
     }
 
     fn field_separator(&self) -> &'static str {
@@ -55,37 +190,58 @@ impl Generator for GleamTypeGenerator {
     }
 
     fn generate_declaration(&self, ident: &str, fields: &str) -> String {
-        format!("pub type {ident} {{\n\t{ident}({fields})\n}}")
+        let head = self.type_head();
+        if fields.is_empty() {
+            // Constructors without fields must not be declared with parentheses
+            format!("pub type {head} {{\n\t{ident}\n}}")
+        } else {
+            format!("pub type {head} {{\n\t{ident}({fields})\n}}")
+        }
     }
 
     fn generate_field(&mut self, field: &Field) -> String {
-        format!("{}: {}", field.ident, self.generate_type_item(&field.ty))
+        format!(
+            "{}: {}",
+            field_name(field),
+            self.generate_type_item(&field.ty)
+        )
     }
 
     fn generate_imports(&self) -> String {
         let mut imports = Vec::<Cow<str>>::new();
 
-        imports.push("import gleam/decode".into());
-        if self.needs_option {
+        imports.push(match self.decode_version {
+            DecodeVersion::Legacy => "import decode".into(),
+            DecodeVersion::Stdlib => "import gleam/dynamic/decode".into(),
+        });
+        imports.push("import gleam/dynamic.{type Dynamic}".into());
+        imports.push("import gleam/json.{type Json}".into());
+        for module in &self.module.key_modules {
+            imports.push(format!("import gleam/{module}").into());
+        }
+        if self.module.needs_option {
             imports.push("import gleam/option.{type Option}".into());
         }
 
-        if self.needs_dict {
+        if self.module.needs_dict {
             imports.push("import gleam/dict.{type Dict}".into());
         }
 
-        for ty in &self.used_types {
-            imports.push(
-                format!(
-                    "import {}/{}.{{type {}}}",
-                    self.module_name,
-                    self.to_file_name(ty),
-                    ty
-                )
-                .into(),
-            )
+        for ty in &self.module.used_types {
+            let import = match split_qualified(ty) {
+                (None, ident) => format!("import {}.{{type {ident}}}", self.module_path(ty)),
+                // Types of packages are referenced through their module, as names can repeat between packages
+                (Some(_), _) => format!(
+                    "import {} as {}",
+                    self.module_path(ty),
+                    self.module_qualifier(ty)
+                ),
+            };
+            imports.push(import.into())
         }
 
+        // Sorted imports keep the output identical between runs
+        imports.sort();
         imports.join("\n")
     }
 
@@ -95,51 +251,71 @@ impl Generator for GleamTypeGenerator {
                 format!("List({})", self.generate_type_item(items))
             }
             TypeItem::Dict { key, value } => {
-                self.needs_dict = true;
+                self.module.needs_dict = true;
                 format!(
-                    "Map({}, {})",
+                    "Dict({}, {})",
                     self.generate_type_item(key),
                     self.generate_type_item(value)
                 )
             }
             TypeItem::Optional(inner) => {
-                self.needs_option = true;
+                self.module.needs_option = true;
                 format!("Option({})", self.generate_type_item(inner))
             }
-            TypeItem::Basic(plain) => match plain.as_str() {
+            TypeItem::Basic(plain, arguments) => match plain.as_str() {
                 "String" => "String".into(),
                 "Int" | "UInt" | "Int8" | "UInt8" | "Int16" | "UInt16" | "Int32" | "UInt32"
                 | "Int64" | "UInt64" | "ISize" | "USize" => "Int".into(),
                 "Bool" => "Bool".into(),
                 "Float" | "Double" => "Float".into(),
+                ty if self.is_type_parameter(ty) => type_parameter(ty),
                 ty => {
-                    self.used_types.insert(ty.to_owned());
-                    ty.to_owned()
+                    // A module must not import itself when its type is recursive
+                    let reference = if self.is_current_type(ty) {
+                        self.module.ident.clone()
+                    } else {
+                        self.module.used_types.insert(ty.to_owned());
+                        self.type_reference(ty)
+                    };
+
+                    if arguments.is_empty() {
+                        reference
+                    } else {
+                        let arguments = arguments
+                            .iter()
+                            .map(|argument| self.generate_type_item(argument))
+                            .collect::<Vec<_>>();
+                        format!("{reference}({})", arguments.join(", "))
+                    }
                 }
             },
         }
     }
 
-    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &OutputFile> + 'a> {
+    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &'a OutputFile> + 'a> {
         Box::new(self.types.iter())
     }
 
     fn reset(&mut self) {
-        self.needs_option = false;
-        self.needs_dict = false;
+        self.module = ModuleState::default();
     }
 
     fn sanitize_ident<'a>(&self, ident: &'a str) -> Cow<'a, str> {
-        // TODO: Sanitize Ident
-        ident.into()
+        escape_keyword(ident)
     }
 
     fn to_file_name(&self, name: &str) -> String {
-        // TODO: Convert to snake case
-        name.to_lowercase()
+        // The file name is also the qualifier of the module, so it must not be a keyword
+        escape_keyword(&to_snake_case(name)).into_owned()
     }
 
-    fn push_type(&mut self, ty: OutputFile) {
+    fn push_type(&mut self, mut ty: OutputFile) {
+        // Files are named by their module path, so that each package is written to its own folder
+        let folder = match &self.module.package {
+            Some(package) => package.replace('.', "/"),
+            None => self.module_name.clone(),
+        };
+        ty.name = format!("{folder}/{}", ty.name);
         self.types.push(ty)
     }
 }
@@ -148,10 +324,9 @@ impl Default for GleamTypeGenerator {
     fn default() -> Self {
         Self {
             types: Vec::new(),
-            needs_option: false,
-            needs_dict: false,
-            used_types: HashSet::new(),
+            module: ModuleState::default(),
             module_name: "types".to_owned(),
+            decode_version: DecodeVersion::default(),
         }
     }
 }
@@ -160,18 +335,196 @@ impl GleamTypeGenerator {
     pub fn new(module_name: String) -> Self {
         Self {
             types: Vec::new(),
-            needs_option: false,
-            needs_dict: false,
-            used_types: HashSet::new(),
+            module: ModuleState::default(),
             module_name,
+            decode_version: DecodeVersion::default(),
         }
     }
 
+    /// Selects the decoding API that generated decoders are written against
+    pub fn with_decode_version(mut self, decode_version: DecodeVersion) -> Self {
+        self.decode_version = decode_version;
+        self
+    }
+
     pub fn boxed() -> Box<Self> {
         Box::default()
     }
 
-    fn type_item_decoder(&self, item: &TypeItem) -> Cow<str> {
+    fn create_legacy_decoder(&self, ty: &Type) -> String {
+        let mut use_statements = Vec::new();
+        let mut constructor_params = Vec::new();
+        let mut field_decoders = Vec::new();
+
+        for field in &ty.fields {
+            let decode_type = self.type_item_decoder(&field.ty);
+            let variable = self.variable_name(field_name(field));
+            use_statements.push(format!("use {variable} <- decode.parameter"));
+            constructor_params.push(variable);
+            field_decoders.push(format!(
+                "|> decode.field({}, {decode_type})",
+                string_literal(&field.wire_name(ty.naming))
+            ));
+        }
+
+        let decoder = if ty.fields.is_empty() {
+            format!("decode.into({})", ty.ident)
+        } else {
+            let use_statements = use_statements.join("\n\t\t");
+            let constructor_params = constructor_params.join(", ");
+            let field_decoders = field_decoders.join("\n\t");
+
+            format!(
+                "decode.into({{\n\t\t{use_statements}\n\n\t\t{}({constructor_params})\n\t}})\n\t{field_decoders}",
+                ty.ident
+            )
+        };
+
+        self.decoder_functions(&format!("\t{decoder}"))
+    }
+
+    fn create_stdlib_decoder(&self, ty: &Type) -> String {
+        let mut use_statements = Vec::new();
+        let mut constructor_params = Vec::new();
+
+        for field in &ty.fields {
+            let decode_type = self.type_item_decoder(&field.ty);
+            let variable = self.variable_name(field_name(field));
+
+            use_statements.push(format!(
+                "\tuse {variable} <- decode.field({}, {decode_type})\n",
+                string_literal(&field.wire_name(ty.naming))
+            ));
+            constructor_params.push(variable);
+        }
+
+        let constructor = if ty.fields.is_empty() {
+            ty.ident.to_string()
+        } else {
+            format!("{}({})", ty.ident, constructor_params.join(", "))
+        };
+
+        self.decoder_functions(&format!(
+            "{}\tdecode.success({constructor})",
+            use_statements.concat()
+        ))
+    }
+
+    /// Renames a local variable that would shadow an imported module, e.g. `json_value` for `json`
+    fn variable_name(&self, mut variable: String) -> String {
+        let is_module = IMPORTED_MODULES.contains(&variable.as_str())
+            || self
+                .module
+                .used_types
+                .iter()
+                .any(|ty| self.module_qualifier(ty) == variable);
+        if is_module {
+            variable.push_str("_value");
+        }
+
+        variable
+    }
+
+    /// Wraps the body of a decoder into the public `decoder` and `decode` functions of the module
+    fn decoder_functions(&self, body: &str) -> String {
+        let decode = match self.decode_version {
+            DecodeVersion::Legacy => {
+                format!("decoder({}) |> decode.from(data)", self.decoder_args())
+            }
+            DecodeVersion::Stdlib => format!("decode.run(data, decoder({}))", self.decoder_args()),
+        };
+
+        format!(
+            "pub fn decoder({}) -> decode.Decoder({}) {{\n{body}\n}}\n\npub fn decode(data: Dynamic{}) {{\n\t{decode}\n}}",
+            self.decoder_params(),
+            self.type_head(),
+            self.decoder_params_suffix(),
+        )
+    }
+
+    /// Whether a qualified type name refers to the type of the current module
+    fn is_current_type(&self, ident: &str) -> bool {
+        qualify(self.module.package.as_deref(), &self.module.ident) == ident
+    }
+
+    /// The path of the module that a qualified type is generated in, e.g. `billing/status`
+    fn module_path(&self, ident: &str) -> String {
+        let (package, ident) = split_qualified(ident);
+        let folder = match package {
+            Some(package) => package.replace('.', "/"),
+            None => self.module_name.clone(),
+        };
+
+        format!("{folder}/{}", self.to_file_name(ident))
+    }
+
+    /// The name that the module of a qualified type is referred to by, e.g. `billing_status`
+    fn module_qualifier(&self, ident: &str) -> String {
+        match split_qualified(ident) {
+            (Some(package), ident) => {
+                format!("{}_{}", package.replace('.', "_"), self.to_file_name(ident))
+            }
+            (None, ident) => self.to_file_name(ident),
+        }
+    }
+
+    /// A reference to a qualified type from another module
+    fn type_reference(&self, ident: &str) -> String {
+        match split_qualified(ident) {
+            (Some(_), name) => format!("{}.{name}", self.module_qualifier(ident)),
+            (None, name) => name.to_owned(),
+        }
+    }
+
+    fn is_type_parameter(&self, ident: &str) -> bool {
+        self.module.generics.iter().any(|param| **param == *ident)
+    }
+
+    /// The current type applied to its type parameters, e.g. `Page(t)`
+    fn type_head(&self) -> String {
+        if self.module.generics.is_empty() {
+            return self.module.ident.clone();
+        }
+
+        let generics = self
+            .module
+            .generics
+            .iter()
+            .map(|generic| type_parameter(generic))
+            .collect::<Vec<_>>();
+        format!("{}({})", self.module.ident, generics.join(", "))
+    }
+
+    /// Parameters of the decoder function, which takes a decoder for each type parameter
+    fn decoder_params(&self) -> String {
+        self.module
+            .generics
+            .iter()
+            .map(|generic| {
+                let generic = type_parameter(generic);
+                format!("{generic}_decoder: decode.Decoder({generic})")
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn decoder_params_suffix(&self) -> String {
+        match self.module.generics.is_empty() {
+            true => "".to_owned(),
+            false => format!(", {}", self.decoder_params()),
+        }
+    }
+
+    fn decoder_args(&self) -> String {
+        self.module
+            .generics
+            .iter()
+            .map(|generic| format!("{}_decoder", type_parameter(generic)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn type_item_decoder(&self, item: &TypeItem) -> Cow<'_, str> {
         match item {
             TypeItem::Array(elements) => {
                 format!("decode.list({})", self.type_item_decoder(elements)).into()
@@ -185,17 +538,167 @@ impl GleamTypeGenerator {
             TypeItem::Optional(inner) => {
                 format!("decode.optional({})", self.type_item_decoder(inner)).into()
             }
-            TypeItem::Basic(plain) => match plain.as_str() {
+            TypeItem::Basic(plain, arguments) => match plain.as_str() {
                 "String" => "decode.string".into(),
                 "Int" | "UInt" | "Int8" | "UInt8" | "Int16" | "UInt16" | "Int32" | "UInt32"
-                | "Int64" | "UInt64" => "decode.int".into(),
+                | "Int64" | "UInt64" | "ISize" | "USize" => "decode.int".into(),
                 "Bool" => "decode.bool".into(),
                 "Float" | "Double" => "decode.float".into(),
-                // This decoder relies on the fact that the other types module will be imported due to the type being used in the struct declaration
-                ty => format!("{}.decode", self.to_file_name(ty)).into(),
+                ty if self.is_type_parameter(ty) => {
+                    format!("{}_decoder", type_parameter(ty)).into()
+                }
+                ty => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|argument| self.type_item_decoder(argument))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    // Decoding a recursive type must defer building its own decoder until data is decoded
+                    if self.is_current_type(ty) {
+                        return match self.decode_version {
+                            DecodeVersion::Legacy => format!(
+                                "decode.dynamic |> decode.then(fn(_) {{ decoder({arguments}) }})"
+                            ),
+                            DecodeVersion::Stdlib if arguments.is_empty() => {
+                                "decode.recursive(decoder)".into()
+                            }
+                            DecodeVersion::Stdlib => {
+                                format!("decode.recursive(fn() {{ decoder({arguments}) }})")
+                            }
+                        }
+                        .into();
+                    }
+
+                    // This decoder relies on the fact that the other types module will be imported due to the type being used in the struct declaration
+                    format!("{}.decoder({arguments})", self.module_qualifier(ty)).into()
+                }
             },
         }
     }
+
+    /// Returns the expression that encodes the value of the given expression to JSON
+    fn type_item_encode(&mut self, item: &TypeItem, value: &str) -> String {
+        match item {
+            TypeItem::Array(elements) => {
+                format!("json.array({value}, {})", self.type_item_encoder(elements))
+            }
+            TypeItem::Dict { key, value: values } => format!(
+                "json.dict({value}, {}, {})",
+                self.dict_key_encoder(key),
+                self.type_item_encoder(values)
+            ),
+            TypeItem::Optional(inner) => {
+                format!("json.nullable({value}, {})", self.type_item_encoder(inner))
+            }
+            TypeItem::Basic(plain, arguments) if !arguments.is_empty() => {
+                let module = match self.is_current_type(plain) {
+                    true => "".to_owned(),
+                    false => format!("{}.", self.module_qualifier(plain)),
+                };
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.type_item_encoder(argument))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!("{module}to_json({value}, {arguments})")
+            }
+            TypeItem::Basic(..) => format!("{}({value})", self.type_item_encoder(item)),
+        }
+    }
+
+    /// Returns a function that encodes values of the given type to JSON
+    fn type_item_encoder(&mut self, item: &TypeItem) -> String {
+        match item {
+            TypeItem::Array(_) | TypeItem::Dict { .. } | TypeItem::Optional(_) => {
+                format!("fn(value) {{ {} }}", self.type_item_encode(item, "value"))
+            }
+            TypeItem::Basic(_, arguments) if !arguments.is_empty() => {
+                format!("fn(value) {{ {} }}", self.type_item_encode(item, "value"))
+            }
+            TypeItem::Basic(plain, _) => match plain.as_str() {
+                "String" => "json.string".into(),
+                "Int" | "UInt" | "Int8" | "UInt8" | "Int16" | "UInt16" | "Int32" | "UInt32"
+                | "Int64" | "UInt64" | "ISize" | "USize" => "json.int".into(),
+                "Bool" => "json.bool".into(),
+                "Float" | "Double" => "json.float".into(),
+                ty if self.is_type_parameter(ty) => format!("{}_to_json", type_parameter(ty)),
+                ty if self.is_current_type(ty) => "to_json".into(),
+                ty => format!("{}.to_json", self.module_qualifier(ty)),
+            },
+        }
+    }
+
+    /// Returns a function that converts dict keys of the given type to the string keys of a JSON object
+    ///
+    /// Declared types are expected to be newtypes of strings as the checker requires, whose wrapped string is the key.
+    /// Keys of schemas that were not checked, like lists or type parameters, are encoded as their JSON text instead.
+    fn dict_key_encoder(&mut self, key: &TypeItem) -> String {
+        let module = match key {
+            TypeItem::Basic(plain, arguments) if arguments.is_empty() => match plain.as_str() {
+                "String" => return "fn(key) { key }".into(),
+                "Int" | "UInt" | "Int8" | "UInt8" | "Int16" | "UInt16" | "Int32" | "UInt32"
+                | "Int64" | "UInt64" | "ISize" | "USize" => "int",
+                "Bool" => "bool",
+                "Float" | "Double" => "float",
+                ty if self.is_type_parameter(ty) => {
+                    return format!(
+                        "fn(key) {{ json.to_string({}) }}",
+                        self.type_item_encode(key, "key")
+                    )
+                }
+                ty if self.is_current_type(ty) => return "value".into(),
+                ty => return format!("{}.value", self.module_qualifier(ty)),
+            },
+            _ => {
+                return format!(
+                    "fn(key) {{ json.to_string({}) }}",
+                    self.type_item_encode(key, "key")
+                )
+            }
+        };
+
+        self.module.key_modules.insert(module);
+        format!("{module}.to_string")
+    }
+}
+
+/// The label of a field in the record constructor, which is independent of the key on the wire
+fn field_name(field: &Field) -> String {
+    escape_keyword(&to_snake_case(&field.ident)).into_owned()
+}
+
+/// Type parameters are written in lowercase, e.g. `t` for `T`
+fn type_parameter(ident: &str) -> String {
+    escape_keyword(&to_snake_case(ident)).into_owned()
+}
+
+/// Gleam has no raw identifiers, so keywords are suffixed with an underscore
+fn escape_keyword(ident: &str) -> Cow<'_, str> {
+    match KEYWORDS.contains(&ident) {
+        true => format!("{ident}_").into(),
+        false => ident.into(),
+    }
+}
+
+/// Writes a Gleam string literal, escaping quotes and control characters
+fn string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            ch => literal.push(ch),
+        }
+    }
+    literal.push('"');
+
+    literal
 }
 
 #[cfg(test)]
@@ -205,7 +708,7 @@ mod test {
         parser::Parser,
     };
 
-    use super::GleamTypeGenerator;
+    use super::{DecodeVersion, GleamTypeGenerator};
 
     #[test]
     fn convert_empty() {
@@ -215,14 +718,14 @@ mod test {
         let ast = parser.parse();
 
         for ty in &ast {
-            exporter.add_type(ty);
+            exporter.add_declaration(ty);
         }
 
         assert_eq!(
             exporter.types,
             vec![OutputFile {
-                name: "empty".to_owned(),
-                content: "import gleam/decode\n\npub type Empty {\n\tEmpty()\n}\n\npub fn decode(data: Dynamic) {\n\tlet decoder = decode.into({\n\t\t\n\n\t\tEmpty()\n\t})\n\t\n\n\tdecoder |> decode.from(data)\n}".to_owned()
+                name: "types/empty".to_owned(),
+                content: "import decode\nimport gleam/dynamic.{type Dynamic}\nimport gleam/json.{type Json}\n\npub type Empty {\n\tEmpty\n}\n\npub fn decoder() -> decode.Decoder(Empty) {\n\tdecode.into(Empty)\n}\n\npub fn decode(data: Dynamic) {\n\tdecoder() |> decode.from(data)\n}\n\npub fn to_json(_empty: Empty) -> Json {\n\tjson.object([])\n}".to_owned()
             }]
         );
     }
@@ -235,15 +738,372 @@ mod test {
         let ast = parser.parse();
 
         for ty in &ast {
-            exporter.add_type(ty);
+            exporter.add_declaration(ty);
         }
 
         assert_eq!(
             exporter.types,
             vec![OutputFile {
-                name: "container".to_owned(),
-                content: "import gleam/decode\n\npub type Container {\n\tContainer(a: Int)\n}\n\npub fn decode(data: Dynamic) {\n\tlet decoder = decode.into({\n\t\tuse a <- decode.parameter\n\n\t\tContainer(a)\n\t})\n\t|> decode.field(\"a\", decode.int)\n\n\tdecoder |> decode.from(data)\n}".to_owned()
+                name: "types/container".to_owned(),
+                content: "import decode\nimport gleam/dynamic.{type Dynamic}\nimport gleam/json.{type Json}\n\npub type Container {\n\tContainer(a: Int)\n}\n\npub fn decoder() -> decode.Decoder(Container) {\n\tdecode.into({\n\t\tuse a <- decode.parameter\n\n\t\tContainer(a)\n\t})\n\t|> decode.field(\"a\", decode.int)\n}\n\npub fn decode(data: Dynamic) {\n\tdecoder() |> decode.from(data)\n}\n\npub fn to_json(container: Container) -> Json {\n\tjson.object([\n\t\t#(\"a\", json.int(container.a)),\n\t])\n}".to_owned()
             }]
         );
     }
+
+    #[test]
+    fn encode_collection_fields() {
+        let source = "type Inventory { owner: User?, tags: [String], counts: {Int: [Item?]} }";
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("import gleam/int\n"));
+        assert!(content.ends_with(
+            "pub fn to_json(inventory: Inventory) -> Json {\n\tjson.object([\n\t\t#(\"owner\", json.nullable(inventory.owner, user.to_json)),\n\t\t#(\"tags\", json.array(inventory.tags, json.string)),\n\t\t#(\"counts\", json.dict(inventory.counts, int.to_string, fn(value) { json.array(value, fn(value) { json.nullable(value, item.to_json) }) })),\n\t])\n}"
+        ));
+    }
+
+    #[test]
+    fn encode_newtype_dict_keys() {
+        let source = "type Inbox { unread: {Email: Int} }";
+        let mut exporter = GleamTypeGenerator::default();
+        for ty in &Parser::new(source).parse() {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content
+            .contains("|> decode.field(\"unread\", decode.dict(email.decoder(), decode.int))"));
+        assert!(content.contains("#(\"unread\", json.dict(inbox.unread, email.value, json.int)),"));
+    }
+
+    #[test]
+    fn encode_unchecked_dict_keys_as_json() {
+        let source = "type Index<K> { byTags: {[String]: Int}, byKey: {K: Int} }";
+        let mut exporter = GleamTypeGenerator::default();
+        for ty in &Parser::new(source).parse() {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("#(\"byTags\", json.dict(index.by_tags, fn(key) { json.to_string(json.array(key, json.string)) }, json.int)),"));
+        assert!(content.contains("#(\"byKey\", json.dict(index.by_key, fn(key) { json.to_string(k_to_json(key)) }, json.int)),"));
+    }
+
+    #[test]
+    fn convert_with_stdlib_decoder() {
+        let source = "type Container { a: Int, item: Item, items: [Item] }";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("import gleam/dynamic/decode\n"));
+        assert!(content.contains(
+            "pub fn decoder() -> decode.Decoder(Container) {\n\tuse a <- decode.field(\"a\", decode.int)\n\tuse item_value <- decode.field(\"item\", item.decoder())\n\tuse items <- decode.field(\"items\", decode.list(item.decoder()))\n\tdecode.success(Container(a, item_value, items))\n}\n\npub fn decode(data: Dynamic) {\n\tdecode.run(data, decoder())\n}"
+        ));
+    }
+
+    #[test]
+    fn convert_empty_with_stdlib_decoder() {
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new("type Empty {}");
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        assert!(exporter.types[0]
+            .content
+            .contains("pub fn decoder() -> decode.Decoder(Empty) {\n\tdecode.success(Empty)\n}"));
+    }
+
+    fn imports(file: &OutputFile) -> Vec<&str> {
+        file.content
+            .lines()
+            .take_while(|line| line.starts_with("import "))
+            .collect()
+    }
+
+    #[test]
+    fn import_only_used_types_per_module() {
+        let source = "type User { account: Account, tags: [Tag]? }
+            type Account { owner: User, balance: {String: Int} }
+            type Tag { label: String }";
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        assert_eq!(
+            imports(&exporter.types[0]),
+            vec![
+                "import decode",
+                "import gleam/dynamic.{type Dynamic}",
+                "import gleam/json.{type Json}",
+                "import gleam/option.{type Option}",
+                "import types/account.{type Account}",
+                "import types/tag.{type Tag}",
+            ]
+        );
+        assert_eq!(
+            imports(&exporter.types[1]),
+            vec![
+                "import decode",
+                "import gleam/dict.{type Dict}",
+                "import gleam/dynamic.{type Dynamic}",
+                "import gleam/json.{type Json}",
+                "import types/user.{type User}",
+            ]
+        );
+        assert_eq!(
+            imports(&exporter.types[2]),
+            vec![
+                "import decode",
+                "import gleam/dynamic.{type Dynamic}",
+                "import gleam/json.{type Json}",
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_type_does_not_import_itself() {
+        let source = "type Person { name: String, children: [Person] }";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(!content.contains("import types/person"));
+        assert!(content.contains(
+            "use children <- decode.field(\"children\", decode.list(decode.recursive(decoder)))"
+        ));
+        assert!(content.contains("#(\"children\", json.array(person.children, to_json))"));
+    }
+
+    #[test]
+    fn convert_names_to_snake_case() {
+        let source = "type Inventory { isAdmin: Bool, armorKind: ArmorKind }";
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("import types/armor_kind.{type ArmorKind}"));
+        assert!(content.contains("Inventory(is_admin: Bool, armor_kind: ArmorKind)"));
+        assert!(content.contains("|> decode.field(\"isAdmin\", decode.bool)"));
+        assert!(content.contains("|> decode.field(\"armorKind\", armor_kind.decoder())"));
+        assert!(content.contains("#(\"isAdmin\", json.bool(inventory.is_admin))"));
+    }
+
+    #[test]
+    fn use_wire_naming_for_keys() {
+        let source = r#"@naming("camelCase") type User { is_admin: Bool, account_id: Int }"#;
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("User(is_admin: Bool, account_id: Int)"));
+        assert!(content.contains("use is_admin <- decode.field(\"isAdmin\", decode.bool)"));
+        assert!(content.contains("use account_id <- decode.field(\"accountId\", decode.int)"));
+        assert!(content.contains("#(\"isAdmin\", json.bool(user.is_admin))"));
+    }
+
+    #[test]
+    fn use_json_annotation_for_keys() {
+        let source = r#"type Legacy { @json("user-id") userId: String, @json("say \"hi\"") greeting: String }"#;
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("|> decode.field(\"user-id\", decode.string)"));
+        assert!(content.contains("|> decode.field(\"say \\\"hi\\\"\", decode.string)"));
+        assert!(content.contains("#(\"user-id\", json.string(legacy.user_id))"));
+    }
+
+    #[test]
+    fn escape_keywords() {
+        let source = "type Container { type: Type, let: [Int], name: String }";
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(content.contains("import types/type_.{type Type}"));
+        assert!(content.contains("Container(type_: Type, let_: List(Int), name: String)"));
+        assert!(content.contains("use type__value <- decode.parameter"));
+        assert!(content.contains("|> decode.field(\"type\", type_.decoder())"));
+        assert!(content.contains("#(\"let\", json.array(container.let_, json.int))"));
+    }
+
+    #[test]
+    fn rename_variables_that_shadow_modules() {
+        let source = "type Json { decode: String, json: Int, dict: {String: Int}, item: Item }";
+
+        for decode_version in [DecodeVersion::Legacy, DecodeVersion::Stdlib] {
+            let mut exporter = GleamTypeGenerator::default().with_decode_version(decode_version);
+            for ty in &Parser::new(source).parse() {
+                exporter.add_declaration(ty);
+            }
+
+            let content = &exporter.types[0].content;
+            assert!(content
+                .contains("Json(decode: String, json: Int, dict: Dict(String, Int), item: Item)"));
+            assert!(content.contains("Json(decode_value, json_value, dict_value, item_value)"));
+            assert!(content.contains("pub fn to_json(json_value: Json) -> Json {\n\tjson.object([\n\t\t#(\"decode\", json.string(json_value.decode)),"));
+        }
+    }
+
+    #[test]
+    fn convert_generics() {
+        let source = "type Page<T> { items: [T], next: String? }
+            type Feed { users: Page<User>, pages: [Page<Int>] }";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let page = &exporter.types[0].content;
+        assert!(
+            page.contains("pub type Page(t) {\n\tPage(items: List(t), next: Option(String))\n}")
+        );
+        assert!(page.contains("pub fn decoder(t_decoder: decode.Decoder(t)) -> decode.Decoder(Page(t)) {\n\tuse items <- decode.field(\"items\", decode.list(t_decoder))\n"));
+        assert!(page.contains("pub fn decode(data: Dynamic, t_decoder: decode.Decoder(t)) {\n\tdecode.run(data, decoder(t_decoder))\n}"));
+        assert!(page.contains("pub fn to_json(page: Page(t), t_to_json: fn(t) -> Json) -> Json {\n\tjson.object([\n\t\t#(\"items\", json.array(page.items, t_to_json)),\n"));
+
+        let feed = &exporter.types[1].content;
+        assert!(feed.contains("import types/page.{type Page}"));
+        assert!(feed.contains("Feed(users: Page(User), pages: List(Page(Int)))"));
+        assert!(feed.contains("use users <- decode.field(\"users\", page.decoder(user.decoder()))"));
+        assert!(feed.contains(
+            "use pages <- decode.field(\"pages\", decode.list(page.decoder(decode.int)))"
+        ));
+        assert!(feed.contains("#(\"users\", page.to_json(feed.users, user.to_json))"));
+        assert!(feed.contains(
+            "#(\"pages\", json.array(feed.pages, fn(value) { page.to_json(value, json.int) }))"
+        ));
+    }
+
+    #[test]
+    fn convert_recursive_generic() {
+        let source = "type Tree<T> { value: T, children: [Tree<T>] }";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let content = &exporter.types[0].content;
+        assert!(!content.contains("import types/tree"));
+        assert!(content.contains("decode.list(decode.recursive(fn() { decoder(t_decoder) }))"));
+        assert!(
+            content.contains("json.array(tree.children, fn(value) { to_json(value, t_to_json) })")
+        );
+    }
+
+    #[test]
+    fn convert_alias() {
+        let source = "alias Friends = [User]";
+        let mut exporter = GleamTypeGenerator::default();
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        assert_eq!(exporter.types[0].name, "types/friends");
+        assert_eq!(
+            exporter.types[0].content,
+            "import decode\nimport gleam/dynamic.{type Dynamic}\nimport gleam/json.{type Json}\nimport types/user.{type User}\n\npub type Friends = List(User)\n\npub fn decoder() -> decode.Decoder(Friends) {\n\tdecode.list(user.decoder())\n}\n\npub fn decode(data: Dynamic) {\n\tdecoder() |> decode.from(data)\n}\n\npub fn to_json(friends: Friends) -> Json {\n\tjson.array(friends, user.to_json)\n}"
+        );
+    }
+
+    #[test]
+    fn convert_newtype() {
+        let source = "newtype Email = String";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        assert_eq!(
+            exporter.types[0].content,
+            "import gleam/dynamic.{type Dynamic}\nimport gleam/dynamic/decode\nimport gleam/json.{type Json}\n\npub opaque type Email {\n\tEmail(String)\n}\n\npub fn new(value: String) -> Email {\n\tEmail(value)\n}\n\npub fn value(email: Email) -> String {\n\tlet Email(value) = email\n\tvalue\n}\n\npub fn decoder() -> decode.Decoder(Email) {\n\tdecode.string |> decode.map(Email)\n}\n\npub fn decode(data: Dynamic) {\n\tdecode.run(data, decoder())\n}\n\npub fn to_json(email: Email) -> Json {\n\tlet Email(value) = email\n\tjson.string(value)\n}"
+        );
+    }
+
+    #[test]
+    fn convert_packages() {
+        let source = "package billing
+            type Invoice { status: billing.Status, history: [billing.Status], owner: User, next: billing.Invoice? }";
+        let mut exporter = GleamTypeGenerator::default().with_decode_version(DecodeVersion::Stdlib);
+        let mut parser = Parser::new(source);
+        let ast = parser.parse();
+
+        for ty in &ast {
+            exporter.add_declaration(ty);
+        }
+
+        let invoice = &exporter.types[0];
+        assert_eq!(invoice.name, "billing/invoice");
+        assert!(invoice
+            .content
+            .contains("import billing/status as billing_status\n"));
+        assert!(invoice.content.contains("import types/user.{type User}\n"));
+        assert!(!invoice.content.contains("import billing/invoice"));
+        assert!(invoice.content.contains("Invoice(status: billing_status.Status, history: List(billing_status.Status), owner: User, next: Option(Invoice))"));
+        assert!(invoice
+            .content
+            .contains("use status <- decode.field(\"status\", billing_status.decoder())"));
+        assert!(invoice.content.contains(
+            "use next <- decode.field(\"next\", decode.optional(decode.recursive(decoder)))"
+        ));
+        assert!(invoice
+            .content
+            .contains("#(\"history\", json.array(invoice.history, billing_status.to_json))"));
+    }
 }
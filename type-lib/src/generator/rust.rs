@@ -1,20 +1,96 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap};
 
-use crate::parser::{Field, Type, TypeItem};
+use crate::{
+    case::{to_snake_case, Case},
+    parser::{split_qualified, Alias, Field, Newtype, Type, TypeItem},
+};
 
 use super::{Generator, OutputFile};
 
+/// Strict and reserved keywords of all editions, which need to be written as raw identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers and are renamed with a trailing underscore instead
+const RESERVED_IDENTS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Traits that are derived for generated structs in addition to the serde traits
+///
+/// Like all derives, they require the type arguments of generic structs to implement the trait as well,
+/// e.g. `Page<T>` only implements `Default` if `T` does, while it can hold any serializable type.
+pub const DEFAULT_DERIVES: &[&str] = &["Clone", "Debug", "Default", "PartialEq"];
+
+/// A declaration in the `mod.rs` of a package
+enum ModuleItem {
+    /// The module of a generated file, which re-exports its type
+    Type {
+        module: String,
+        ident: String,
+        content: String,
+    },
+    /// A nested package, by its module and its qualified name
+    Package { module: String, package: String },
+}
+
 pub struct RustTypeGenerator {
-    module: OutputFile,
+    /// The `mod.rs` of each package, where the root package has an empty name
+    modules: BTreeMap<String, OutputFile>,
+    /// The declarations of each `mod.rs` in order, to nest the files into a single module
+    items: BTreeMap<String, Vec<ModuleItem>>,
     types: Vec<OutputFile>,
+    /// The package path of the current declaration
+    package: Vec<String>,
+    /// The wire naming convention of the current type
+    naming: Option<Case>,
+    /// The type parameters of the current type
+    generics: Vec<Box<str>>,
+    derives: Vec<String>,
 }
 
 impl Generator for RustTypeGenerator {
+    fn begin_package(&mut self, package: Option<&str>) {
+        self.package = package
+            .map(|package| package.split('.').map(str::to_owned).collect())
+            .unwrap_or_default();
+    }
+
+    fn begin_type(&mut self, ty: &Type) {
+        self.naming = ty.naming;
+        self.generics = ty.generics.clone();
+    }
+
     fn add_type_boilerplate(&mut self, ty: &Type, file: &OutputFile) {
-        self.module.content.push_str(&format!(
-            "mod {};\npub use {}::{};\n",
-            file.name, file.name, ty.ident
-        ))
+        self.export(&ty.ident, file)
+    }
+
+    fn add_alias(&mut self, alias: &Alias) {
+        let declaration = format!(
+            "pub type {} = {};",
+            self.sanitize_ident(&alias.ident),
+            self.generate_type_item(&alias.ty)
+        );
+        let file = self.create_file(&alias.ident, &[declaration]);
+        self.export(&alias.ident, &file);
+
+        self.push_type(file);
+    }
+
+    fn add_newtype(&mut self, newtype: &Newtype) {
+        let declaration = format!(
+            "{}\n#[serde(transparent)]\npub struct {}(pub {});",
+            self.derive(),
+            self.sanitize_ident(&newtype.ident),
+            self.generate_type_item(&newtype.ty)
+        );
+        let file = self.create_file(&newtype.ident, &[declaration]);
+        self.export(&newtype.ident, &file);
+
+        self.push_type(file);
     }
 
     fn field_separator(&self) -> &'static str {
@@ -30,17 +106,40 @@ impl Generator for RustTypeGenerator {
     }
 
     fn generate_declaration(&self, ident: &str, fields: &str) -> String {
+        let ident = self.sanitize_ident(ident);
+
+        if self.generics.is_empty() {
+            return format!("{}\npub struct {ident} {{\n{fields}\n}}", self.derive());
+        }
+
+        let bounds = |bound: &str| {
+            self.generics
+                .iter()
+                .map(|param| format!("{param}: {bound}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
         format!(
-            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct {ident} {{\n{fields}\n}}"
+            "{}\n#[serde(bound(serialize = \"{}\", deserialize = \"{}\"))]\npub struct {ident}<{}> {{\n{fields}\n}}",
+            self.derive(),
+            bounds("serde::Serialize"),
+            bounds("serde::de::DeserializeOwned"),
+            self.generics.join(", ")
         )
     }
 
     fn generate_field(&mut self, field: &Field) -> String {
-        format!(
-            "\tpub {}: {}",
-            field.ident,
-            self.generate_type_item(&field.ty)
-        )
+        let name = to_snake_case(&field.ident);
+        let name = self.sanitize_ident(&name);
+        let wire_name = field.wire_name(self.naming);
+        let ty = self.generate_type_item(&field.ty);
+
+        // Serde uses raw identifiers without their prefix, but renamed keywords need to keep their key
+        if name.trim_start_matches("r#") == wire_name {
+            format!("\tpub {name}: {ty}")
+        } else {
+            format!("\t#[serde(rename = {wire_name:?})]\n\tpub {name}: {ty}")
+        }
     }
 
     fn generate_type_item(&mut self, ty: &TypeItem) -> String {
@@ -52,7 +151,7 @@ impl Generator for RustTypeGenerator {
                 self.generate_type_item(value)
             ),
             TypeItem::Optional(inner) => format!("Option<{}>", self.generate_type_item(inner)),
-            TypeItem::Basic(plain) => match plain.as_str() {
+            TypeItem::Basic(plain, arguments) => match plain.as_str() {
                 "String" => "String".into(),
                 "Int" | "Int64" => "i64".into(),
                 "UInt" | "UInt64" => "u64".into(),
@@ -67,41 +166,73 @@ impl Generator for RustTypeGenerator {
                 "Bool" => "bool".into(),
                 "Float" => "f32".into(),
                 "Double" => "f64".into(),
-                ty => format!("super::{ty}").into(),
+                ty if self.generics.iter().any(|param| **param == *ty) => ty.to_owned(),
+                ty if arguments.is_empty() => self.type_path(ty),
+                ty => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|argument| self.generate_type_item(argument))
+                        .collect::<Vec<_>>();
+                    format!("{}<{}>", self.type_path(ty), arguments.join(", "))
+                }
             },
         }
     }
 
-    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &OutputFile> + 'a> {
-        Box::new(self.types.iter().chain(std::iter::once(&self.module)))
+    fn output_dyn<'a>(&'a self) -> Box<dyn Iterator<Item = &'a OutputFile> + 'a> {
+        Box::new(self.types.iter().chain(self.modules.values()))
+    }
+
+    fn reset(&mut self) {
+        self.naming = None;
+        self.generics.clear();
     }
 
     fn sanitize_ident<'a>(&self, ident: &'a str) -> Cow<'a, str> {
-        match ident {
-            // TODO: Escape more keywords
-            "type" => format!("r#{ident}").into(),
-            _ => ident.into(),
+        if KEYWORDS.contains(&ident) {
+            format!("r#{ident}").into()
+        } else if RESERVED_IDENTS.contains(&ident) {
+            format!("{ident}_").into()
+        } else {
+            ident.into()
         }
     }
 
     fn to_file_name(&self, name: &str) -> String {
-        // TODO: Convert to snake case
-        name.to_lowercase()
+        // Raw identifiers refer to a file without the prefix, so only renamed keywords need a different file name
+        let name = to_snake_case(name);
+        match RESERVED_IDENTS.contains(&name.as_str()) {
+            true => format!("{name}_"),
+            false => name,
+        }
     }
 
-    fn push_type(&mut self, ty: OutputFile) {
+    fn push_type(&mut self, mut ty: OutputFile) {
+        if !self.package.is_empty() {
+            ty.name = format!("{}/{}", self.package.join("/"), ty.name);
+        }
         self.types.push(ty)
     }
 }
 
 impl Default for RustTypeGenerator {
     fn default() -> Self {
+        let root = OutputFile {
+            name: "mod".to_owned(),
+            content: "".to_owned(),
+        };
+
         Self {
-            module: OutputFile {
-                name: "mod".to_owned(),
-                content: "".to_owned(),
-            },
+            modules: BTreeMap::from([(String::new(), root)]),
+            items: BTreeMap::new(),
             types: Vec::new(),
+            package: Vec::new(),
+            naming: None,
+            generics: Vec::new(),
+            derives: DEFAULT_DERIVES
+                .iter()
+                .map(|derive| derive.to_string())
+                .collect(),
         }
     }
 }
@@ -111,7 +242,328 @@ impl RustTypeGenerator {
         Self::default()
     }
 
+    /// Replaces the traits that are derived next to `serde::Serialize` and `serde::Deserialize`
+    pub fn with_derives(mut self, derives: Vec<String>) -> Self {
+        self.derives = derives;
+        self
+    }
+
     pub fn boxed() -> Box<Self> {
         Box::default()
     }
+
+    fn derive(&self) -> String {
+        let derives = ["serde::Serialize", "serde::Deserialize"]
+            .into_iter()
+            .chain(self.derives.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+
+        format!("#[derive({})]", derives.join(", "))
+    }
+
+    /// Declares the module of a generated file and re-exports its type from the `mod.rs` of its package
+    fn export(&mut self, ident: &str, file: &OutputFile) {
+        let module = self.sanitize_ident(&file.name).into_owned();
+        let ident = self.sanitize_ident(ident).into_owned();
+        let export = format!("mod {module};\npub use {module}::{ident};\n");
+
+        self.package_module().content.push_str(&export);
+        self.items
+            .entry(self.package.join("."))
+            .or_default()
+            .push(ModuleItem::Type {
+                module,
+                ident,
+                content: file.content.clone(),
+            });
+    }
+
+    /// The `mod.rs` of the current package, declaring it in its parent packages if it is new
+    fn package_module(&mut self) -> &mut OutputFile {
+        for depth in 1..=self.package.len() {
+            let key = self.package[..depth].join(".");
+            if self.modules.contains_key(&key) {
+                continue;
+            }
+
+            let child = self.sanitize_ident(&self.package[depth - 1]).into_owned();
+            let parent = self.package[..depth - 1].join(".");
+            if let Some(module) = self.modules.get_mut(&parent) {
+                module.content.push_str(&format!("pub mod {child};\n"));
+            }
+            self.items
+                .entry(parent)
+                .or_default()
+                .push(ModuleItem::Package {
+                    module: child,
+                    package: key.clone(),
+                });
+
+            let module = OutputFile {
+                name: format!("{}/mod", self.package[..depth].join("/")),
+                content: "".to_owned(),
+            };
+            self.modules.insert(key, module);
+        }
+
+        let key = self.package.join(".");
+        self.modules.get_mut(&key).unwrap()
+    }
+
+    /// All generated types in a single module, where files and packages are nested as inline modules
+    /// instead of being declared in a `mod.rs`, so that it can be included from one file
+    pub fn inline_module(&self) -> String {
+        self.inline_package("")
+    }
+
+    fn inline_package(&self, package: &str) -> String {
+        let items = self
+            .items
+            .get(package)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        items
+            .iter()
+            .map(|item| match item {
+                ModuleItem::Type {
+                    module,
+                    ident,
+                    content,
+                } => format!(
+                    "mod {module} {{\n{}\n}}\npub use {module}::{ident};",
+                    indent(content)
+                ),
+                ModuleItem::Package { module, package } => {
+                    format!(
+                        "pub mod {module} {{\n{}\n}}",
+                        indent(&self.inline_package(package))
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The path to a declared type, relative to the module of the current type
+    fn type_path(&self, ident: &str) -> String {
+        let (package, ident) = split_qualified(ident);
+
+        // Leave the module of the type and all packages it is nested in to reach the root package
+        let mut path = "super::".repeat(1 + self.package.len());
+        for segment in package.into_iter().flat_map(|package| package.split('.')) {
+            path.push_str(&self.sanitize_ident(segment));
+            path.push_str("::");
+        }
+        path.push_str(&self.sanitize_ident(ident));
+
+        path
+    }
+}
+
+/// Indents all non-empty lines by a tab
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| match line.is_empty() {
+            true => String::new(),
+            false => format!("\t{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{generator::Generator, parser::Parser};
+
+    use super::RustTypeGenerator;
+
+    #[test]
+    fn convert_names_to_snake_case() {
+        let source = "type ArmorKind { isAdmin: Bool, level: Int }";
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(exporter.types[0].name, "armor_kind");
+        assert_eq!(
+            exporter.types[0].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct ArmorKind {\n\t#[serde(rename = \"isAdmin\")]\n\tpub is_admin: bool,\n\tpub level: i64\n}"
+        );
+        assert_eq!(
+            exporter.modules[""].content,
+            "mod armor_kind;\npub use armor_kind::ArmorKind;\n"
+        );
+    }
+
+    #[test]
+    fn rename_fields_to_wire_naming() {
+        let source = r#"naming "camelCase"
+            type User { is_admin: Bool, level: Int }
+            @naming("kebab-case") type Account { user_id: String }"#;
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert!(exporter.types[0].content.contains(
+            "\t#[serde(rename = \"isAdmin\")]\n\tpub is_admin: bool,\n\tpub level: i64\n"
+        ));
+        assert!(exporter.types[1]
+            .content
+            .contains("\t#[serde(rename = \"user-id\")]\n\tpub user_id: String\n"));
+    }
+
+    #[test]
+    fn rename_fields_with_json_annotation() {
+        let source = r#"naming "camelCase"
+            type Legacy { @json("@type") kind: String, @json("user_id") userId: String }"#;
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            exporter.types[0].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct Legacy {\n\t#[serde(rename = \"@type\")]\n\tpub kind: String,\n\tpub user_id: String\n}"
+        );
+    }
+
+    #[test]
+    fn escape_keywords() {
+        let source = "type Type { type: String, self: Int, match: Bool, crate: Self }
+            type Self { super: [Type] }";
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(exporter.types[0].name, "type");
+        assert_eq!(
+            exporter.types[0].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct Type {\n\tpub r#type: String,\n\t#[serde(rename = \"self\")]\n\tpub self_: i64,\n\tpub r#match: bool,\n\t#[serde(rename = \"crate\")]\n\tpub crate_: super::Self_\n}"
+        );
+        assert_eq!(exporter.types[1].name, "self_");
+        assert_eq!(
+            exporter.types[1].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct Self_ {\n\t#[serde(rename = \"super\")]\n\tpub super_: Vec<super::Type>\n}"
+        );
+        assert_eq!(
+            exporter.modules[""].content,
+            "mod r#type;\npub use r#type::Type;\nmod self_;\npub use self_::Self_;\n"
+        );
+    }
+
+    #[test]
+    fn convert_generics() {
+        let source = "type Page<T> { items: [T], next: String? }
+            type Feed { users: Page<User>, nested: Page<Page<Int>> }";
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            exporter.types[0].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\n#[serde(bound(serialize = \"T: serde::Serialize\", deserialize = \"T: serde::de::DeserializeOwned\"))]\npub struct Page<T> {\n\tpub items: Vec<T>,\n\tpub next: Option<String>\n}"
+        );
+        assert_eq!(
+            exporter.types[1].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\npub struct Feed {\n\tpub users: super::Page<super::User>,\n\tpub nested: super::Page<super::Page<i64>>\n}"
+        );
+    }
+
+    #[test]
+    fn convert_alias_and_newtype() {
+        let source = "alias UserIds = [UserId]
+            newtype UserId = UInt64
+            newtype Type = String";
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(exporter.types[0].name, "user_ids");
+        assert_eq!(
+            exporter.types[0].content,
+            "pub type UserIds = Vec<super::UserId>;"
+        );
+        assert_eq!(
+            exporter.types[1].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\n#[serde(transparent)]\npub struct UserId(pub u64);"
+        );
+        assert_eq!(
+            exporter.types[2].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]\n#[serde(transparent)]\npub struct Type(pub String);"
+        );
+        assert_eq!(
+            exporter.modules[""].content,
+            "mod user_ids;\npub use user_ids::UserIds;\nmod user_id;\npub use user_id::UserId;\nmod r#type;\npub use r#type::Type;\n"
+        );
+    }
+
+    #[test]
+    fn convert_with_custom_derives() {
+        let source = "newtype Slot = String";
+        let mut exporter =
+            RustTypeGenerator::new().with_derives(vec!["Clone".to_owned(), "Hash".to_owned()]);
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(
+            exporter.types[0].content,
+            "#[derive(serde::Serialize, serde::Deserialize, Clone, Hash)]\n#[serde(transparent)]\npub struct Slot(pub String);"
+        );
+    }
+
+    #[test]
+    fn convert_packages() {
+        let source = "package billing.invoices
+            type Invoice { status: billing.Status, owner: User, items: [billing.invoices.Item] }";
+        let mut exporter = RustTypeGenerator::default();
+        for declaration in Parser::new(source).parse() {
+            exporter.add_declaration(&declaration);
+        }
+
+        assert_eq!(exporter.types[0].name, "billing/invoices/invoice");
+        assert!(exporter.types[0].content.contains(
+            "\tpub status: super::super::super::billing::Status,\n\tpub owner: super::super::super::User,\n\tpub items: Vec<super::super::super::billing::invoices::Item>\n"
+        ));
+
+        let modules = exporter
+            .output_dyn()
+            .skip(1)
+            .map(|file| (file.name.as_str(), file.content.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modules,
+            vec![
+                ("mod", "pub mod billing;\n"),
+                ("billing/mod", "pub mod invoices;\n"),
+                (
+                    "billing/invoices/mod",
+                    "mod invoice;\npub use invoice::Invoice;\n"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_packages() {
+        let mut exporter = RustTypeGenerator::new().with_derives(Vec::new());
+        for source in [
+            "type User { status: billing.Type }",
+            "package billing\nnewtype Type = String",
+        ] {
+            for declaration in Parser::new(source).parse() {
+                exporter.add_declaration(&declaration);
+            }
+        }
+
+        assert_eq!(
+            exporter.inline_module(),
+            "mod user {\n\t#[derive(serde::Serialize, serde::Deserialize)]\n\tpub struct User {\n\t\tpub status: super::billing::Type\n\t}\n}\npub use user::User;\npub mod billing {\n\tmod r#type {\n\t\t#[derive(serde::Serialize, serde::Deserialize)]\n\t\t#[serde(transparent)]\n\t\tpub struct Type(pub String);\n\t}\n\tpub use r#type::Type;\n}"
+        );
+    }
 }
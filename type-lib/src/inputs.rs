@@ -0,0 +1,144 @@
+//! Resolves the glob patterns of the schemas to generate, for the command line and for build scripts alike.
+
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+use glob::{glob, GlobError, PatternError};
+
+/// Finds the schemas matching the patterns relative to a root directory, in the order of the patterns
+///
+/// The files matching a single pattern are sorted by their path and files matching several patterns are only returned once.
+pub fn resolve(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>, InputError> {
+    let mut inputs = Vec::new();
+
+    for pattern in patterns {
+        let full_pattern = root.join(pattern);
+        let paths = glob(&full_pattern.to_string_lossy()).map_err(|error| InputError::Invalid {
+            pattern: pattern.clone(),
+            error,
+        })?;
+
+        let mut matched = Vec::new();
+        for path in paths {
+            let path = path.map_err(InputError::Io)?;
+            if path.is_file() {
+                matched.push(path);
+            }
+        }
+        if matched.is_empty() {
+            return Err(InputError::NoMatch(pattern.clone()));
+        }
+
+        matched.sort();
+        for path in matched {
+            if !inputs.contains(&path) {
+                inputs.push(path);
+            }
+        }
+    }
+
+    Ok(inputs)
+}
+
+/// The directory before the first segment of a pattern that contains a wildcard,
+/// which contains all files that the pattern can match
+pub fn base_dir(pattern: &str) -> PathBuf {
+    let base = Path::new(pattern)
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '['])
+        })
+        .collect::<PathBuf>();
+
+    match base.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => base,
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// A pattern is not a valid glob pattern
+    Invalid {
+        pattern: String,
+        error: PatternError,
+    },
+    /// A pattern does not match any file
+    NoMatch(String),
+    /// A directory could not be read while matching a pattern
+    Io(GlobError),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Invalid { pattern, error } => {
+                write!(f, "Invalid input pattern '{pattern}': {error}")
+            }
+            InputError::NoMatch(pattern) => {
+                write!(f, "The input pattern '{pattern}' does not match any file")
+            }
+            InputError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::PathBuf};
+
+    use super::{base_dir, resolve, InputError};
+
+    #[test]
+    fn resolve_patterns() {
+        let root = env::temp_dir().join(format!("type-lang-inputs-{}", std::process::id()));
+        fs::create_dir_all(root.join("schemas/billing")).unwrap();
+        for file in [
+            "schemas/user.type",
+            "schemas/account.type",
+            "schemas/billing/invoice.type",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let patterns =
+            ["schemas/user.type", "schemas/**/*.type", "schemas/[ab]*"].map(String::from);
+        let inputs = resolve(&root, &patterns);
+        let missing = resolve(&root, &["*.type".to_owned()]);
+        let invalid = resolve(&root, &["schemas/[".to_owned()]);
+        fs::remove_dir_all(&root).unwrap();
+
+        let relative = inputs
+            .unwrap()
+            .into_iter()
+            .map(|input| input.strip_prefix(&root).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            relative,
+            [
+                "schemas/user.type",
+                "schemas/account.type",
+                "schemas/billing/invoice.type"
+            ]
+            .map(PathBuf::from)
+        );
+        assert!(matches!(missing, Err(InputError::NoMatch(pattern)) if pattern == "*.type"));
+        assert!(matches!(invalid, Err(InputError::Invalid { .. })));
+    }
+
+    #[test]
+    fn find_base_dir() {
+        assert_eq!(base_dir("schema/*.type"), PathBuf::from("schema"));
+        assert_eq!(base_dir("schema/**/*.type"), PathBuf::from("schema"));
+        assert_eq!(base_dir("schema/[ab].type"), PathBuf::from("schema"));
+        assert_eq!(base_dir("*.type"), PathBuf::from("."));
+        assert_eq!(base_dir("user.type"), PathBuf::from("user.type"));
+    }
+}
@@ -1,3 +1,10 @@
+pub mod build;
+pub mod case;
+pub mod checker;
+pub mod formatter;
 pub mod generator;
+pub mod inputs;
+pub mod loader;
+pub mod outline;
 pub mod parser;
 mod tokenizer;
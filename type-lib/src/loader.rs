@@ -0,0 +1,645 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Display},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::parser::{
+    split_qualified, Declaration, ParseError, Parser, Schema, TypeItem, BUILTIN_TYPES,
+};
+
+/// Reads the source of a schema file
+type Reader = Box<dyn Fn(&Path) -> io::Result<String>>;
+
+/// Loads a schema file together with all schema files that it imports
+pub struct Loader {
+    read: Reader,
+    /// The source and parsed schema of each loaded file, so that loading again only parses files that changed
+    cache: RefCell<HashMap<PathBuf, (String, Schema)>>,
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::with_reader(|path| fs::read_to_string(path))
+    }
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a loader that reads schema files with the given function instead of from the file system
+    pub fn with_reader(read: impl Fn(&Path) -> io::Result<String> + 'static) -> Self {
+        Self {
+            read: Box::new(read),
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Loads the schema at the given path and all of its imports into a combined symbol table
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<SymbolTable, LoadError> {
+        self.load_all([path])
+    }
+
+    /// Loads several schemas into one symbol table, where files imported by more than one of them are only loaded once
+    pub fn load_all(
+        &self,
+        paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> Result<SymbolTable, LoadError> {
+        let mut stack = Vec::new();
+        let mut loaded = HashSet::new();
+        let mut symbols = SymbolTable {
+            hash: FNV_OFFSET,
+            ..SymbolTable::default()
+        };
+
+        for path in paths {
+            self.load_file(
+                &normalize(path.as_ref()),
+                &mut stack,
+                &mut loaded,
+                &mut symbols,
+            )?;
+        }
+        symbols.resolve()?;
+
+        Ok(symbols)
+    }
+
+    /// Loads a file after its imports, so that declarations are ordered by their dependencies
+    fn load_file(
+        &self,
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut HashSet<PathBuf>,
+        symbols: &mut SymbolTable,
+    ) -> Result<(), LoadError> {
+        if let Some(start) = stack.iter().position(|file| file == path) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(path.to_owned());
+            return Err(LoadError::Cycle(cycle));
+        }
+
+        // A file that is imported by multiple files is only loaded once
+        if !loaded.insert(path.to_owned()) {
+            return Ok(());
+        }
+
+        symbols.files.push(path.to_owned());
+        let (source, schema) = self.parse(path)?;
+        symbols.hash = fnv1a(symbols.hash, source.as_bytes());
+
+        // Imports are resolved relative to the directory of the importing file
+        let directory = path.parent().unwrap_or(Path::new(""));
+        stack.push(path.to_owned());
+        for import in &schema.imports {
+            self.load_file(
+                &normalize(&directory.join(&**import)),
+                stack,
+                loaded,
+                symbols,
+            )?;
+        }
+        stack.pop();
+
+        for declaration in schema.declarations {
+            symbols.insert(path, declaration)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads and parses a file, unless it has not changed since it was last parsed by this loader
+    fn parse(&self, path: &Path) -> Result<(String, Schema), LoadError> {
+        let source = (self.read)(path).map_err(|error| LoadError::Io {
+            path: path.to_owned(),
+            error,
+        })?;
+
+        if let Some((cached, schema)) = self.cache.borrow().get(path) {
+            if *cached == source {
+                return Ok((source, schema.clone()));
+            }
+        }
+
+        let schema = Parser::new(&source)
+            .parse_schema()
+            .map_err(|error| LoadError::Parse {
+                path: path.to_owned(),
+                error,
+            })?;
+        self.cache
+            .borrow_mut()
+            .insert(path.to_owned(), (source.clone(), schema.clone()));
+
+        Ok((source, schema))
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Continues a 64 bit FNV-1a hash with more bytes
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// All declarations of a schema and the schemas it imports
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    /// Declarations in the order they were loaded, where imported declarations come first
+    declarations: Vec<Declaration>,
+    /// The index of each declaration by its qualified name, together with the file it is declared in
+    symbols: BTreeMap<Box<str>, (usize, PathBuf)>,
+    /// All loaded files, where the imports of a file come after it
+    files: Vec<PathBuf>,
+    /// The hash of the sources of all loaded files
+    hash: u64,
+}
+
+impl SymbolTable {
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    /// Looks up a declaration by its qualified name
+    pub fn get(&self, ident: &str) -> Option<&Declaration> {
+        self.symbols
+            .get(ident)
+            .map(|(index, _)| &self.declarations[*index])
+    }
+
+    /// The paths of all loaded schema files
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// A hash of the sources of all loaded files, which is stable across platforms and versions
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The file that a declaration is declared in
+    pub fn path(&self, ident: &str) -> Option<&Path> {
+        self.symbols.get(ident).map(|(_, path)| path.as_path())
+    }
+
+    /// The qualified name of the declaration that a type name refers to from within a package,
+    /// unless there is no such declaration or the name is ambiguous
+    pub fn lookup(&self, ident: &str, package: Option<&str>) -> Option<&str> {
+        match self.candidates(ident, package).as_slice() {
+            [candidate] => Some(candidate),
+            _ => None,
+        }
+    }
+
+    /// The declarations that a type name can refer to from within a package, which are more than one if it is ambiguous
+    ///
+    /// Unqualified names refer to a type of the same package, or to the only type with that name in any package
+    fn candidates(&self, ident: &str, package: Option<&str>) -> Vec<&str> {
+        if let Some(package) = package.filter(|_| !ident.contains('.')) {
+            if let Some((qualified, _)) = self.symbols.get_key_value(&*format!("{package}.{ident}"))
+            {
+                return vec![qualified];
+            }
+        }
+
+        if let Some((qualified, _)) = self.symbols.get_key_value(ident) {
+            return vec![qualified];
+        }
+        if ident.contains('.') {
+            return Vec::new();
+        }
+
+        self.symbols
+            .keys()
+            .filter(|symbol| split_qualified(symbol).1 == ident)
+            .map(|symbol| &**symbol)
+            .collect()
+    }
+
+    fn insert(&mut self, path: &Path, declaration: Declaration) -> Result<(), LoadError> {
+        let ident = declaration.qualified_ident();
+        if let Some((_, first)) = self.symbols.get(&*ident) {
+            return Err(LoadError::Duplicate {
+                ident: ident.into(),
+                first: first.clone(),
+                second: path.to_owned(),
+            });
+        }
+
+        self.symbols
+            .insert(ident.into(), (self.declarations.len(), path.to_owned()));
+        self.declarations.push(declaration);
+
+        Ok(())
+    }
+
+    /// Qualifies all references to declared types with their package
+    fn resolve(&mut self) -> Result<(), LoadError> {
+        let mut declarations = std::mem::take(&mut self.declarations);
+        let result = declarations.iter_mut().try_for_each(|declaration| {
+            let package = declaration.package().map(str::to_owned);
+            let generics = declaration.generics().to_vec();
+            let path = self.symbols[&*declaration.qualified_ident()].1.clone();
+
+            declaration
+                .type_items_mut()
+                .into_iter()
+                .try_for_each(|item| self.resolve_item(item, package.as_deref(), &generics, &path))
+        });
+        self.declarations = declarations;
+
+        result
+    }
+
+    /// Qualifies a type name with the package of the declaration that it refers to
+    fn resolve_item(
+        &self,
+        item: &mut TypeItem,
+        package: Option<&str>,
+        generics: &[Box<str>],
+        path: &Path,
+    ) -> Result<(), LoadError> {
+        match item {
+            TypeItem::Array(inner) | TypeItem::Optional(inner) => {
+                self.resolve_item(inner, package, generics, path)
+            }
+            TypeItem::Dict { key, value } => {
+                self.resolve_item(key, package, generics, path)?;
+                self.resolve_item(value, package, generics, path)
+            }
+            TypeItem::Basic(ident, arguments) => {
+                for argument in arguments.iter_mut() {
+                    self.resolve_item(argument, package, generics, path)?;
+                }
+
+                let is_parameter = generics.iter().any(|param| **param == **ident);
+                if BUILTIN_TYPES.contains(&ident.as_str()) || is_parameter {
+                    return Ok(());
+                }
+
+                match self.candidates(ident, package).as_slice() {
+                    [] => Ok(()),
+                    [candidate] => {
+                        *ident = candidate.to_string();
+                        Ok(())
+                    }
+                    candidates => Err(LoadError::Ambiguous {
+                        ident: ident.as_str().into(),
+                        path: path.to_owned(),
+                        candidates: candidates
+                            .iter()
+                            .map(|&candidate| candidate.into())
+                            .collect(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    /// A schema file could not be read
+    Io { path: PathBuf, error: io::Error },
+    /// A schema file has a syntax error
+    Parse { path: PathBuf, error: ParseError },
+    /// Files import each other, starting and ending with the same file
+    Cycle(Vec<PathBuf>),
+    /// An unqualified type name refers to types of multiple packages
+    Ambiguous {
+        ident: Box<str>,
+        path: PathBuf,
+        candidates: Vec<Box<str>>,
+    },
+    /// A type is declared in more than one place
+    Duplicate {
+        ident: Box<str>,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, error } => {
+                write!(f, "Could not read '{}': {error}", path.display())
+            }
+            LoadError::Parse { path, error } => write!(f, "{}: {error}", path.display()),
+            LoadError::Cycle(cycle) => {
+                let cycle = cycle
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "Import cycle: {}", cycle.join(" -> "))
+            }
+            LoadError::Ambiguous {
+                ident,
+                path,
+                candidates,
+            } => write!(
+                f,
+                "Type '{ident}' in '{}' is ambiguous and needs to be qualified with one of {}",
+                path.display(),
+                candidates.join(", ")
+            ),
+            LoadError::Duplicate {
+                ident,
+                first,
+                second,
+            } => write!(
+                f,
+                "Type '{ident}' is declared in '{}' and again in '{}'",
+                first.display(),
+                second.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Resolves `.` and `..` components without touching the file system, so that every file has a single path
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, io, path::Path, rc::Rc};
+
+    use super::*;
+
+    fn loader(files: &[(&str, &str)]) -> Loader {
+        let files = files
+            .iter()
+            .map(|&(path, source)| (Path::new(path).to_owned(), source.to_owned()))
+            .collect::<HashMap<_, _>>();
+
+        Loader::with_reader(move |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        })
+    }
+
+    fn idents(symbols: &SymbolTable) -> Vec<&str> {
+        symbols
+            .declarations()
+            .iter()
+            .map(Declaration::ident)
+            .collect()
+    }
+
+    #[test]
+    fn load_imports_relative_to_file() {
+        let loader = loader(&[
+            (
+                "schema/user.type",
+                r#"import "account/account.type"
+                type User { account: Account }"#,
+            ),
+            (
+                "schema/account/account.type",
+                r#"import "../tag.type"
+                type Account { tags: [Tag] }"#,
+            ),
+            ("schema/tag.type", "type Tag { label: String }"),
+        ]);
+        let symbols = loader.load("./schema/user.type").unwrap();
+
+        assert_eq!(idents(&symbols), vec!["Tag", "Account", "User"]);
+        assert_eq!(symbols.path("Tag"), Some(Path::new("schema/tag.type")));
+        assert_eq!(symbols.get("Account").unwrap().ident(), "Account");
+        assert!(symbols.get("Missing").is_none());
+    }
+
+    #[test]
+    fn load_shared_import_once() {
+        let loader = loader(&[
+            (
+                "user.type",
+                r#"import "account.type"
+                import "tag.type"
+                type User { account: Account, tags: [Tag] }"#,
+            ),
+            (
+                "account.type",
+                r#"import "tag.type"
+                type Account { tags: [Tag] }"#,
+            ),
+            ("tag.type", "type Tag { label: String }"),
+        ]);
+        let symbols = loader.load("user.type").unwrap();
+
+        assert_eq!(idents(&symbols), vec!["Tag", "Account", "User"]);
+    }
+
+    #[test]
+    fn load_several_schemas() {
+        let loader = loader(&[
+            (
+                "user.type",
+                r#"import "tag.type"
+                type User { tags: [Tag] }"#,
+            ),
+            (
+                "post.type",
+                r#"import "tag.type"
+                type Post { tags: [Tag], author: User }"#,
+            ),
+            ("tag.type", "type Tag { label: String }"),
+        ]);
+        let symbols = loader.load_all(["user.type", "post.type"]).unwrap();
+
+        assert_eq!(idents(&symbols), vec!["Tag", "User", "Post"]);
+        assert_eq!(
+            symbols.files(),
+            ["user.type", "tag.type", "post.type"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn hash_sources() {
+        assert_eq!(fnv1a(FNV_OFFSET, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn reload_changed_files() {
+        let source = Rc::new(RefCell::new("type User { name: String }".to_owned()));
+        let loader = {
+            let source = source.clone();
+            Loader::with_reader(move |_| Ok(source.borrow().clone()))
+        };
+
+        let symbols = loader.load("user.type").unwrap();
+        assert_eq!(idents(&symbols), vec!["User"]);
+        let reloaded = loader.load("user.type").unwrap();
+        assert_eq!(idents(&reloaded), vec!["User"]);
+        assert_eq!(reloaded.hash(), symbols.hash());
+
+        *source.borrow_mut() = "type Account { id: String }".to_owned();
+        let changed = loader.load("user.type").unwrap();
+        assert_eq!(idents(&changed), vec!["Account"]);
+        assert_ne!(changed.hash(), symbols.hash());
+    }
+
+    #[test]
+    fn reject_import_cycle() {
+        let loader = loader(&[
+            ("a.type", r#"import "b.type""#),
+            ("b.type", r#"import "c.type""#),
+            ("c.type", r#"import "./b.type""#),
+        ]);
+        let error = loader.load("a.type").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Import cycle: b.type -> c.type -> b.type"
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_declaration() {
+        let loader = loader(&[
+            (
+                "user.type",
+                r#"import "status.type"
+                alias Status = String"#,
+            ),
+            ("status.type", "type Status { code: Int }"),
+        ]);
+        let error = loader.load("user.type").unwrap_err();
+
+        assert!(matches!(
+            error,
+            LoadError::Duplicate { ref ident, .. } if &**ident == "Status"
+        ));
+    }
+
+    #[test]
+    fn reject_missing_import() {
+        let loader = loader(&[("user.type", r#"import "missing.type""#)]);
+        let error = loader.load("user.type").unwrap_err();
+
+        assert!(
+            matches!(error, LoadError::Io { ref path, .. } if path == Path::new("missing.type"))
+        );
+    }
+
+    #[test]
+    fn qualify_references_by_package() {
+        let loader = loader(&[
+            (
+                "order.type",
+                r#"import "billing.type"
+                import "shipping.type"
+                type Order { billing: billing.Status, shipping: shipping.Status, invoice: Invoice }"#,
+            ),
+            (
+                "billing.type",
+                "package billing
+                type Status { paid: Bool }
+                type Invoice { status: Status, history: [Status] }",
+            ),
+            (
+                "shipping.type",
+                "package shipping
+                type Status { delivered: Bool }",
+            ),
+        ]);
+        let symbols = loader.load("order.type").unwrap();
+
+        let qualified = symbols
+            .declarations()
+            .iter()
+            .map(Declaration::qualified_ident)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            qualified,
+            vec![
+                "billing.Status",
+                "billing.Invoice",
+                "shipping.Status",
+                "Order"
+            ]
+        );
+
+        let invoice = symbols.get("billing.Invoice").unwrap().as_type().unwrap();
+        assert_eq!(invoice.fields[0].ty.to_string(), "billing.Status");
+        assert_eq!(invoice.fields[1].ty.to_string(), "[billing.Status]");
+
+        let order = symbols.get("Order").unwrap().as_type().unwrap();
+        assert_eq!(order.fields[2].ty.to_string(), "billing.Invoice");
+
+        assert_eq!(
+            symbols.lookup("Status", Some("billing")),
+            Some("billing.Status")
+        );
+        assert_eq!(
+            symbols.lookup("shipping.Status", Some("billing")),
+            Some("shipping.Status")
+        );
+        assert_eq!(symbols.lookup("Invoice", None), Some("billing.Invoice"));
+        assert_eq!(symbols.lookup("Status", None), None);
+    }
+
+    #[test]
+    fn reject_ambiguous_reference() {
+        let loader = loader(&[
+            (
+                "order.type",
+                r#"import "billing.type"
+                import "shipping.type"
+                type Order { status: Status }"#,
+            ),
+            ("billing.type", "package billing\ntype Status {}"),
+            ("shipping.type", "package shipping\ntype Status {}"),
+        ]);
+        let error = loader.load("order.type").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Type 'Status' in 'order.type' is ambiguous and needs to be qualified with one of billing.Status, shipping.Status"
+        );
+    }
+
+    #[test]
+    fn reject_syntax_error_in_import() {
+        let loader = loader(&[
+            ("user.type", r#"import "tag.type""#),
+            ("tag.type", "type Tag { label String }"),
+        ]);
+        let error = loader.load("user.type").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "tag.type: Unexpected 'String' at line 1, column 18"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Locates type names in the text of a schema, for tools like editors that need to know where a name is written.
+//!
+//! Unlike the parser, the outline is built from the tokens alone, so it is also available while a schema has syntax errors.
+
+use std::ops::Range;
+
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+
+/// What a type name in a schema stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// The name of a type, alias or newtype where it is declared
+    Declaration,
+    /// A type parameter of a declaration or a reference to it
+    Parameter,
+    /// A reference to a built-in or declared type, e.g. in the type of a field
+    Reference,
+}
+
+/// A type name in a schema
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    /// The name as it is written, which includes its package if it is qualified, e.g. `billing.Status`
+    pub ident: String,
+    pub kind: SymbolKind,
+    /// The byte range of the name in the source
+    pub range: Range<usize>,
+}
+
+/// The type names of a schema with their locations
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Outline {
+    /// The package that the declarations belong to
+    pub package: Option<String>,
+    /// The paths of the imported files together with the byte range of their string literal
+    pub imports: Vec<(String, Range<usize>)>,
+    /// All type names in the order they are written
+    pub symbols: Vec<Symbol>,
+}
+
+impl Outline {
+    pub fn new(source: &str) -> Self {
+        let mut lexer = Tokenizer::new(source);
+        let mut tokens = Vec::new();
+        loop {
+            let token = lexer.next().into_keyword();
+            match token.kind {
+                TokenKind::Eof => break,
+                TokenKind::Newline => continue,
+                _ => tokens.push(token),
+            }
+        }
+
+        let mut outline = Outline::default();
+        // The type parameters of the declaration that the tokens belong to
+        let mut generics = Vec::new();
+        let mut index = 0;
+        while let Some(token) = tokens.get(index) {
+            let next = tokens.get(index + 1).map(|token| token.kind);
+            index += 1;
+
+            match (token.kind, next) {
+                (
+                    TokenKind::TypeKeyword | TokenKind::AliasKeyword | TokenKind::NewtypeKeyword,
+                    Some(TokenKind::TypeIdent),
+                ) => {
+                    outline.push(&tokens[index], SymbolKind::Declaration);
+                    index += 1;
+                    generics.clear();
+
+                    if token.kind == TokenKind::TypeKeyword
+                        && tokens.get(index).map(|token| token.kind) == Some(TokenKind::AngleOpen)
+                    {
+                        index += 1;
+                        while let Some(parameter) = tokens.get(index).filter(|token| {
+                            matches!(token.kind, TokenKind::TypeIdent | TokenKind::Comma)
+                        }) {
+                            if parameter.kind == TokenKind::TypeIdent {
+                                generics.push(parameter.str.to_string());
+                                outline.push(parameter, SymbolKind::Parameter);
+                            }
+                            index += 1;
+                        }
+                    }
+                }
+                (TokenKind::PackageKeyword, Some(TokenKind::Ident)) => {
+                    let (segments, end) = qualifier(&tokens, index);
+                    outline.package = Some(segments.join("."));
+                    index = end;
+                }
+                (TokenKind::ImportKeyword, Some(TokenKind::StringLiteral)) => {
+                    let path = &tokens[index];
+                    if let Ok(unquoted) = path.unquote() {
+                        outline.imports.push((unquoted, range(path)));
+                    }
+                    index += 1;
+                }
+                (TokenKind::Ident, Some(TokenKind::Dot)) => {
+                    let (mut segments, end) = qualifier(&tokens, index - 1);
+                    // A qualifier is only part of a type name if it is followed by one, e.g. `billing.Status`
+                    if let Some(ident) = tokens
+                        .get(end)
+                        .filter(|token| token.kind == TokenKind::TypeIdent)
+                    {
+                        segments.push(&ident.str);
+                        outline.symbols.push(Symbol {
+                            ident: segments.join("."),
+                            kind: SymbolKind::Reference,
+                            range: token.span.start()..range(ident).end,
+                        });
+                        index = end + 1;
+                    }
+                }
+                (TokenKind::TypeIdent, _) => {
+                    let kind = match generics.iter().any(|generic| *generic == *token.str) {
+                        true => SymbolKind::Parameter,
+                        false => SymbolKind::Reference,
+                    };
+                    outline.push(token, kind);
+                }
+                _ => {}
+            }
+        }
+
+        outline
+    }
+
+    fn push(&mut self, token: &Token, kind: SymbolKind) {
+        self.symbols.push(Symbol {
+            ident: token.str.to_string(),
+            kind,
+            range: range(token),
+        });
+    }
+
+    /// The type name at a byte offset, which includes the offset right after the name
+    pub fn symbol_at(&self, offset: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.range.start <= offset && offset <= symbol.range.end)
+    }
+
+    /// The declaration of a type by its unqualified name
+    pub fn declaration(&self, ident: &str) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.kind == SymbolKind::Declaration && symbol.ident == ident)
+    }
+}
+
+/// The segments of a dotted name of lowercase identifiers, e.g. `billing.invoices`, and the index of the token after it
+fn qualifier(tokens: &[Token], mut index: usize) -> (Vec<&str>, usize) {
+    let mut segments = Vec::new();
+    while let Some(segment) = tokens
+        .get(index)
+        .filter(|token| token.kind == TokenKind::Ident)
+    {
+        segments.push(&*segment.str);
+        index += 1;
+        match tokens.get(index).map(|token| token.kind) {
+            Some(TokenKind::Dot) => index += 1,
+            _ => break,
+        }
+    }
+
+    (segments, index)
+}
+
+fn range(token: &Token) -> Range<usize> {
+    token.span.start()..token.span.start() + token.str.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Outline, SymbolKind};
+
+    fn symbols(outline: &Outline) -> Vec<(&str, SymbolKind)> {
+        outline
+            .symbols
+            .iter()
+            .map(|symbol| (symbol.ident.as_str(), symbol.kind))
+            .collect()
+    }
+
+    #[test]
+    fn outline_declarations_and_references() {
+        let source = r#"package shop.orders
+import "billing.type"
+
+type Page<T, U> { items: [T], next: Page<User>? }
+alias Tags = [Tag]
+newtype Email = String
+type Order { invoice: billing.Invoice, type: Int }"#;
+        let outline = Outline::new(source);
+
+        assert_eq!(outline.package.as_deref(), Some("shop.orders"));
+        assert_eq!(outline.imports, vec![("billing.type".to_owned(), 27..41)]);
+        assert_eq!(
+            symbols(&outline),
+            vec![
+                ("Page", SymbolKind::Declaration),
+                ("T", SymbolKind::Parameter),
+                ("U", SymbolKind::Parameter),
+                ("T", SymbolKind::Parameter),
+                ("Page", SymbolKind::Reference),
+                ("User", SymbolKind::Reference),
+                ("Tags", SymbolKind::Declaration),
+                ("Tag", SymbolKind::Reference),
+                ("Email", SymbolKind::Declaration),
+                ("String", SymbolKind::Reference),
+                ("Order", SymbolKind::Declaration),
+                ("billing.Invoice", SymbolKind::Reference),
+                ("Int", SymbolKind::Reference),
+            ]
+        );
+
+        let invoice = source.find("billing.Invoice").unwrap();
+        let symbol = outline
+            .symbol_at(invoice + "billing.Invoice".len())
+            .unwrap();
+        assert_eq!(symbol.range, invoice..invoice + "billing.Invoice".len());
+        assert_eq!(
+            outline.declaration("Email").unwrap().range.start,
+            source.find("Email").unwrap()
+        );
+    }
+
+    #[test]
+    fn outline_incomplete_schema() {
+        let outline = Outline::new("type User { name: Str\ntype Tag { label: → }");
+
+        assert_eq!(
+            symbols(&outline),
+            vec![
+                ("User", SymbolKind::Declaration),
+                ("Str", SymbolKind::Reference),
+                ("Tag", SymbolKind::Declaration),
+            ]
+        );
+    }
+}
@@ -1,62 +1,311 @@
-use crate::tokenizer::{Token, TokenKind, Tokenizer};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+};
+
+use crate::{
+    case::Case,
+    tokenizer::{Token, TokenKind, Tokenizer},
+};
+
+/// Names of the types that are built into the language and never refer to a declaration
+pub const BUILTIN_TYPES: &[&str] = &[
+    "String", "Int", "UInt", "Int8", "UInt8", "Int16", "UInt16", "Int32", "UInt32", "Int64",
+    "UInt64", "ISize", "USize", "Bool", "Float", "Double",
+];
 
 pub struct Parser<'a> {
     lexer: Tokenizer<'a>,
+    /// The naming convention on the wire for all types in the file
+    naming: Option<Case>,
+    /// The package that all types in the file belong to
+    package: Option<Box<str>>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
             lexer: Tokenizer::new(source),
+            naming: None,
+            package: None,
+        }
+    }
+
+    /// Parses all declarations up to the end of the file or the first syntax error
+    pub fn parse(&mut self) -> Vec<Declaration> {
+        self.parse_partial().0.declarations
+    }
+
+    /// Parses a whole schema file, including the paths of the files that it imports
+    pub fn parse_schema(&mut self) -> Result<Schema, ParseError> {
+        match self.parse_partial() {
+            (_, Some(error)) => Err(error),
+            (schema, None) => Ok(schema),
+        }
+    }
+
+    /// Parses the schema up to the end of the file or the first syntax error, which is returned alongside
+    fn parse_partial(&mut self) -> (Schema, Option<ParseError>) {
+        let mut imports = vec![];
+        let mut declarations = vec![];
+        let mut header_comments = vec![];
+        let mut end_comments = vec![];
+        let mut error = None;
+        loop {
+            let comments = self.lexer.leading_comments();
+            let result = match self.lexer.peek_keyword() {
+                TokenKind::Eof => {
+                    end_comments = comments;
+                    break;
+                }
+                // The naming convention must be known before the declarations that it applies to
+                TokenKind::NamingKeyword if !declarations.is_empty() => {
+                    Err(self.lexer.next_skip_newline())
+                }
+                TokenKind::NamingKeyword => self.parse_naming().map(|_| None),
+                TokenKind::ImportKeyword => self.parse_import().map(|path| {
+                    imports.push(path);
+                    None
+                }),
+                TokenKind::PackageKeyword => self.parse_package().map(|_| None),
+                TokenKind::AliasKeyword => self
+                    .parse_alias()
+                    .map(|alias| Some(Declaration::Alias(alias))),
+                TokenKind::NewtypeKeyword => self
+                    .parse_newtype()
+                    .map(|newtype| Some(Declaration::Newtype(newtype))),
+                _ => self
+                    .parse_declaration()
+                    .map(|ty| Some(Declaration::Type(ty))),
+            };
+
+            match result {
+                // Comments of the package, naming and imports are written above all of them
+                Ok(None) => header_comments.extend(comments),
+                Ok(Some(mut declaration)) => {
+                    declaration.comments_mut().leading = comments;
+                    declarations.push(declaration);
+                }
+                Err(token) => {
+                    error = Some(self.error(token));
+                    break;
+                }
+            }
+        }
+
+        // The file-wide naming convention applies to all types that do not declare their own
+        for declaration in &mut declarations {
+            if let Declaration::Type(ty) = declaration {
+                ty.naming = ty.naming.or(self.naming);
+            }
+            declaration.set_package(self.package.clone());
         }
+
+        let schema = Schema {
+            package: self.package.clone(),
+            naming: self.naming,
+            imports,
+            declarations,
+            comments: header_comments,
+            end_comments,
+        };
+
+        (schema, error)
     }
 
-    pub fn parse(&mut self) -> Vec<Type> {
-        let mut types = vec![];
-        while let Ok(ty) = self.parse_declaration() {
-            types.push(ty);
+    /// Locates an unexpected token in the source
+    fn error(&self, token: Token) -> ParseError {
+        let before = &self.lexer.source()[..token.span.start()];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+
+        let found = match token.kind {
+            TokenKind::Eof => "end of file".into(),
+            TokenKind::Newline => "line break".into(),
+            _ => format!("'{}'", token.str).into(),
+        };
+
+        ParseError {
+            line,
+            column,
+            found,
         }
+    }
+
+    /// Parses an import of another schema file, e.g. `import "account.type"`
+    fn parse_import(&mut self) -> Result<Box<str>, Token> {
+        self.lexer.expect(TokenKind::ImportKeyword)?;
+        let path = self.lexer.expect(TokenKind::StringLiteral)?;
 
-        types
+        Ok(path.unquote()?.into())
+    }
+
+    /// Parses the package of the file, e.g. `package billing.invoices`
+    fn parse_package(&mut self) -> Result<(), Token> {
+        self.lexer.expect(TokenKind::PackageKeyword)?;
+
+        let mut segments = vec![self.lexer.expect(TokenKind::Ident)?.str];
+        while self.lexer.try_next(TokenKind::Dot).is_some() {
+            segments.push(self.lexer.expect(TokenKind::Ident)?.str);
+        }
+        self.package = Some(segments.join(".").into());
+
+        Ok(())
+    }
+
+    /// Parses a file-wide naming convention directive, e.g. `naming "camelCase"`, which must be written before all declarations
+    fn parse_naming(&mut self) -> Result<(), Token> {
+        self.lexer.expect(TokenKind::NamingKeyword)?;
+        self.naming = Some(self.parse_case()?);
+
+        Ok(())
+    }
+
+    fn parse_case(&mut self) -> Result<Case, Token> {
+        let literal = self.lexer.expect(TokenKind::StringLiteral)?;
+        literal.unquote()?.parse().map_err(|_| literal)
+    }
+
+    /// Parses an annotation of the form `@name("argument")` and returns the name token and the argument token
+    fn parse_annotation(&mut self) -> Result<(Token, Token), Token> {
+        self.lexer.expect(TokenKind::At)?;
+        let ident = self.lexer.expect(TokenKind::Ident)?;
+        self.lexer.expect(TokenKind::ParenOpen)?;
+        let argument = self.lexer.expect(TokenKind::StringLiteral)?;
+        self.lexer.expect(TokenKind::ParenClose)?;
+
+        Ok((ident, argument))
+    }
+
+    /// Parses a type alias, e.g. `alias UserId = String`
+    fn parse_alias(&mut self) -> Result<Alias, Token> {
+        self.lexer.expect(TokenKind::AliasKeyword)?;
+        let (ident, ty) = self.parse_definition()?;
+
+        Ok(Alias {
+            ident,
+            ty,
+            package: None,
+            comments: Comments {
+                leading: Vec::new(),
+                trailing: self.lexer.trailing_comment(),
+            },
+        })
+    }
+
+    /// Parses a newtype, e.g. `newtype Email = String`
+    fn parse_newtype(&mut self) -> Result<Newtype, Token> {
+        self.lexer.expect(TokenKind::NewtypeKeyword)?;
+        let (ident, ty) = self.parse_definition()?;
+
+        Ok(Newtype {
+            ident,
+            ty,
+            package: None,
+            comments: Comments {
+                leading: Vec::new(),
+                trailing: self.lexer.trailing_comment(),
+            },
+        })
+    }
+
+    /// Parses the `Name = Type` part of an alias or newtype
+    fn parse_definition(&mut self) -> Result<(Box<str>, TypeItem), Token> {
+        let ident = self.lexer.expect(TokenKind::TypeIdent)?;
+        self.lexer.expect(TokenKind::Equals)?;
+        let ty = self.parse_type_item()?;
+
+        Ok((ident.str, ty))
     }
 
     fn parse_declaration(&mut self) -> Result<Type, Token> {
+        let mut naming = None;
+        while self.lexer.peek_keyword() == TokenKind::At {
+            let (ident, argument) = self.parse_annotation()?;
+            match &*ident.str {
+                "naming" => {
+                    naming = Some(argument.unquote()?.parse().map_err(|_| argument)?);
+                }
+                _ => return Err(ident),
+            }
+        }
+
         self.lexer.expect(TokenKind::TypeKeyword)?;
         let ident = self.lexer.expect(TokenKind::TypeIdent)?;
 
+        let mut generics = Vec::new();
+        if self.lexer.try_next(TokenKind::AngleOpen).is_some() {
+            loop {
+                generics.push(self.lexer.expect(TokenKind::TypeIdent)?.str);
+                if self.lexer.try_next(TokenKind::Comma).is_none() {
+                    break;
+                }
+            }
+            self.lexer.expect(TokenKind::AngleClose)?;
+        }
+
         self.lexer.expect(TokenKind::BraceOpen)?;
 
         let mut fields = Vec::new();
-        loop {
-            match self.lexer.peek() {
+        let end_comments = loop {
+            let comments = self.lexer.leading_comments();
+            match self.lexer.peek_keyword() {
                 TokenKind::BraceClose => {
                     self.lexer.next_skip_newline();
-                    break;
+                    break comments;
+                }
+                TokenKind::Eof => return Err(self.lexer.next_skip_newline()),
+                _ => {
+                    let mut field = self.parse_field()?;
+                    field.comments.leading = comments;
+                    fields.push(field);
                 }
-                TokenKind::EOF => todo!("Parser Error: Missing closing brace!"),
-                _ => fields.push(self.parse_field()?),
             }
-        }
+        };
 
         Ok(Type {
-            ident: ident.str.into(),
+            ident: ident.str,
+            generics,
             fields,
+            naming,
+            package: None,
+            comments: Comments {
+                leading: Vec::new(),
+                trailing: self.lexer.trailing_comment(),
+            },
+            end_comments,
         })
     }
 
     fn parse_field(&mut self) -> Result<Field, Token> {
+        let mut json = None;
+        while self.lexer.peek_keyword() == TokenKind::At {
+            let (ident, argument) = self.parse_annotation()?;
+            match &*ident.str {
+                "json" => json = Some(argument.unquote()?.into()),
+                _ => return Err(ident),
+            }
+        }
+
         let ident = self.lexer.expect(TokenKind::Ident)?;
         self.lexer.expect(TokenKind::Colon)?;
 
         let ty = self.parse_type_item()?;
 
-        while self.lexer.peek().is_delim() {
+        // Line breaks after the field are left for the comments of the next field
+        while self.lexer.peek() == TokenKind::Comma {
             self.lexer.next();
         }
+        let trailing = self.lexer.trailing_comment();
 
         Ok(Field {
             ident: ident.str,
             ty,
+            json,
+            comments: Comments {
+                leading: Vec::new(),
+                trailing,
+            },
         })
     }
 
@@ -78,35 +327,237 @@ impl<'a> Parser<'a> {
 
                 TypeItem::Array(element)
             }
-            TokenKind::ParenOpen => todo!("Parse tuple type"),
-            TokenKind::TypeIdent => {
-                let ident = self.lexer.expect(TokenKind::TypeIdent).unwrap();
+            TokenKind::TypeIdent | TokenKind::Ident => {
+                // Types of other packages are qualified with the package name, e.g. `billing.Status`
+                let mut ident = String::new();
+                while self.lexer.peek() == TokenKind::Ident {
+                    ident.push_str(&self.lexer.next().str);
+                    ident.push_str(&self.lexer.expect(TokenKind::Dot)?.str);
+                }
+                ident.push_str(&self.lexer.expect(TokenKind::TypeIdent)?.str);
+
+                let mut arguments = Vec::new();
+                if self.lexer.try_next(TokenKind::AngleOpen).is_some() {
+                    loop {
+                        arguments.push(self.parse_type_item()?);
+                        if self.lexer.try_next(TokenKind::Comma).is_none() {
+                            break;
+                        }
+                    }
+                    self.lexer.expect(TokenKind::AngleClose)?;
+                }
 
-                TypeItem::Basic(ident.str.into())
+                TypeItem::Basic(ident, arguments)
             }
-            TokenKind::EOF => todo!("Parser Error: Expected type item, found EOF!"),
-            token => todo!("Parser Error: Expected type item, found {:#?}", token),
+            _ => return Err(self.lexer.next()),
         };
 
-        while let Some(_) = self.lexer.try_next(TokenKind::QuestionMark) {
+        while self.lexer.try_next(TokenKind::QuestionMark).is_some() {
             ty = TypeItem::Optional(Box::new(ty));
         }
 
-        return Ok(ty);
+        Ok(ty)
+    }
+}
+
+/// A syntax error in a schema
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line of the unexpected token, starting at 1
+    pub line: usize,
+    /// The column of the unexpected token in bytes, starting at 1
+    pub column: usize,
+    /// A description of the unexpected token
+    pub found: Box<str>,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unexpected {} at line {}, column {}",
+            self.found, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The contents of a single schema file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    /// The package that all declarations of the file belong to
+    pub package: Option<Box<str>>,
+    /// The file-wide naming convention, which is already applied to the declarations that do not have their own
+    pub naming: Option<Case>,
+    /// Paths of the imported schema files, relative to the importing file
+    pub imports: Vec<Box<str>>,
+    pub declarations: Vec<Declaration>,
+    /// Comments before the package, naming convention and imports
+    pub comments: Vec<Box<str>>,
+    /// Comments after the last declaration
+    pub end_comments: Vec<Box<str>>,
+}
+
+/// The comments around a declaration or field, which are kept when formatting a schema
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Comments {
+    /// Comments on the lines above, where an empty comment stands for blank lines between them
+    pub leading: Vec<Box<str>>,
+    /// A comment at the end of the last line
+    pub trailing: Option<Box<str>>,
+}
+
+/// A top-level declaration in a schema
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Declaration {
+    Type(Type),
+    Alias(Alias),
+    Newtype(Newtype),
+}
+
+impl Declaration {
+    /// The name of the declared type
+    pub fn ident(&self) -> &str {
+        match self {
+            Declaration::Type(ty) => &ty.ident,
+            Declaration::Alias(alias) => &alias.ident,
+            Declaration::Newtype(newtype) => &newtype.ident,
+        }
+    }
+
+    /// The package that the type is declared in, if any
+    pub fn package(&self) -> Option<&str> {
+        match self {
+            Declaration::Type(ty) => ty.package.as_deref(),
+            Declaration::Alias(alias) => alias.package.as_deref(),
+            Declaration::Newtype(newtype) => newtype.package.as_deref(),
+        }
+    }
+
+    /// The name of the declared type, qualified with its package, e.g. `billing.Status`
+    pub fn qualified_ident(&self) -> String {
+        qualify(self.package(), self.ident())
+    }
+
+    pub fn comments(&self) -> &Comments {
+        match self {
+            Declaration::Type(ty) => &ty.comments,
+            Declaration::Alias(alias) => &alias.comments,
+            Declaration::Newtype(newtype) => &newtype.comments,
+        }
+    }
+
+    fn comments_mut(&mut self) -> &mut Comments {
+        match self {
+            Declaration::Type(ty) => &mut ty.comments,
+            Declaration::Alias(alias) => &mut alias.comments,
+            Declaration::Newtype(newtype) => &mut newtype.comments,
+        }
     }
+
+    fn set_package(&mut self, package: Option<Box<str>>) {
+        match self {
+            Declaration::Type(ty) => ty.package = package,
+            Declaration::Alias(alias) => alias.package = package,
+            Declaration::Newtype(newtype) => newtype.package = package,
+        }
+    }
+
+    /// The type parameters of the declaration
+    pub fn generics(&self) -> &[Box<str>] {
+        match self {
+            Declaration::Type(ty) => &ty.generics,
+            Declaration::Alias(_) | Declaration::Newtype(_) => &[],
+        }
+    }
+
+    /// All type items that are referenced by the declaration
+    pub fn type_items_mut(&mut self) -> Vec<&mut TypeItem> {
+        match self {
+            Declaration::Type(ty) => ty.fields.iter_mut().map(|field| &mut field.ty).collect(),
+            Declaration::Alias(Alias { ty, .. }) | Declaration::Newtype(Newtype { ty, .. }) => {
+                vec![ty]
+            }
+        }
+    }
+
+    pub fn as_type(&self) -> Option<&Type> {
+        match self {
+            Declaration::Type(ty) => Some(ty),
+            _ => None,
+        }
+    }
+}
+
+/// Another name for an existing type, which is interchangeable with it, e.g. `alias UserId = String`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alias {
+    pub ident: Box<str>,
+    pub ty: TypeItem,
+    pub package: Option<Box<str>>,
+    pub comments: Comments,
+}
+
+/// A distinct type that wraps an existing type and is serialized like it, e.g. `newtype Email = String`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Newtype {
+    pub ident: Box<str>,
+    pub ty: TypeItem,
+    pub package: Option<Box<str>>,
+    pub comments: Comments,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Type {
     pub ident: Box<str>,
+    /// Names of the type parameters, e.g. `T` in `type Page<T>`
+    pub generics: Vec<Box<str>>,
     pub fields: Vec<Field>,
+    /// The naming convention of field keys on the wire, if they are not written as in the schema
+    pub naming: Option<Case>,
+    /// The package that the type is declared in, if any
+    pub package: Option<Box<str>>,
+    pub comments: Comments,
+    /// Comments after the last field
+    pub end_comments: Vec<Box<str>>,
     // span: Span,
 }
 
+/// Qualifies the name of a type with its package, e.g. `billing.Status`
+pub fn qualify(package: Option<&str>, ident: &str) -> String {
+    match package {
+        Some(package) => format!("{package}.{ident}"),
+        None => ident.to_owned(),
+    }
+}
+
+/// Splits a possibly qualified type name into its package and its name
+pub fn split_qualified(ident: &str) -> (Option<&str>, &str) {
+    match ident.rsplit_once('.') {
+        Some((package, ident)) => (Some(package), ident),
+        None => (None, ident),
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Field {
     pub ident: Box<str>,
     pub ty: TypeItem,
+    /// The key of the field in JSON, overriding any naming convention
+    pub json: Option<Box<str>>,
+    pub comments: Comments,
+}
+
+impl Field {
+    /// The key of the field in serialized data, given the naming convention of its type
+    pub fn wire_name(&self, naming: Option<Case>) -> Cow<'_, str> {
+        match (&self.json, naming) {
+            (Some(json), _) => (**json).into(),
+            (None, Some(case)) => case.convert(&self.ident).into(),
+            (None, None) => (*self.ident).into(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -118,7 +569,34 @@ pub enum TypeItem {
     },
     Optional(Box<TypeItem>),
 
-    Basic(String),
+    /// A reference to a built-in or declared type with its generic arguments, e.g. `Page<User>`
+    Basic(String, Vec<TypeItem>),
+}
+
+impl TypeItem {
+    /// Returns true if this is a reference to a built-in type
+    pub fn is_builtin(&self) -> bool {
+        matches!(self, TypeItem::Basic(ident, _) if BUILTIN_TYPES.contains(&ident.as_str()))
+    }
+}
+
+impl Display for TypeItem {
+    /// Formats the type item in the syntax of the type language
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeItem::Array(element) => write!(f, "[{element}]"),
+            TypeItem::Dict { key, value } => write!(f, "{{{key}: {value}}}"),
+            TypeItem::Optional(inner) => write!(f, "{inner}?"),
+            TypeItem::Basic(ident, arguments) if arguments.is_empty() => write!(f, "{ident}"),
+            TypeItem::Basic(ident, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                write!(f, "{ident}<{}>", arguments.join(", "))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -150,7 +628,37 @@ mod test {
         let source = "          ";
         let mut parser = Parser::new(source);
 
-        assert!(matches!(parser.parse_declaration(), Err(_)));
+        assert!(parser.parse_declaration().is_err());
+    }
+
+    #[test]
+    fn test_parse_generics() {
+        let source =
+            "type Page<T, Cursor> { items: [T], next: Cursor?, nested: Page<Page<T>, String> }";
+        let mut parser = Parser::new(source);
+        let ty = parser.parse_declaration().unwrap();
+
+        assert_eq!(ty.generics, vec!["T".into(), "Cursor".into()]);
+        assert_eq!(
+            ty.fields[2].ty,
+            TypeItem::Basic(
+                "Page".into(),
+                vec![
+                    TypeItem::Basic("Page".into(), vec![TypeItem::Basic("T".into(), vec![])]),
+                    TypeItem::Basic("String".into(), vec![]),
+                ]
+            )
+        );
+        assert_eq!(ty.fields[2].ty.to_string(), "Page<Page<T>, String>");
+    }
+
+    #[test]
+    fn test_display_type_item() {
+        let source = "type Nested { a: {String: [Int?]}? }";
+        let mut parser = Parser::new(source);
+        let ty = parser.parse_declaration().unwrap();
+
+        assert_eq!(ty.fields[0].ty.to_string(), "{String: [Int?]}?");
     }
 
     #[test]
@@ -168,13 +676,257 @@ mod test {
             vec![
                 Field {
                     ident: "a".into(),
-                    ty: TypeItem::Basic("Int".into())
+                    ty: TypeItem::Basic("Int".into(), vec![]),
+                    json: None,
+                    comments: Comments::default(),
                 },
                 Field {
                     ident: "b".into(),
-                    ty: TypeItem::Basic("String".into())
+                    ty: TypeItem::Basic("String".into(), vec![]),
+                    json: None,
+                    comments: Comments::default(),
                 }
             ]
         );
     }
+
+    #[test]
+    fn test_parse_empty_type_with_newline() {
+        let source = "type Empty {\n}";
+        let mut parser = Parser::new(source);
+        let ty = parser.parse_declaration().unwrap();
+
+        assert_eq!(ty.fields, vec![]);
+    }
+
+    #[test]
+    fn test_parse_naming() {
+        let source = r#"
+            naming "camelCase"
+
+            @naming("snake_case")
+            type Own { a: Int }
+            type After { a: Int }
+        "#;
+        let mut parser = Parser::new(source);
+        let declarations = parser.parse();
+        let types = declarations
+            .iter()
+            .filter_map(Declaration::as_type)
+            .collect::<Vec<_>>();
+
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[0].naming, Some(Case::Snake));
+        assert_eq!(types[1].naming, Some(Case::Camel));
+    }
+
+    #[test]
+    fn test_reject_naming_after_declaration() {
+        let source = "type Before { a: Int }\nnaming \"camelCase\"";
+        let error = Parser::new(source).parse_schema().unwrap_err();
+
+        assert_eq!(error.to_string(), "Unexpected 'naming' at line 2, column 1");
+    }
+
+    #[test]
+    fn test_reject_unknown_naming() {
+        let source = r#"@naming("Title Case") type Test {}"#;
+        let mut parser = Parser::new(source);
+        let error = parser.parse_declaration().unwrap_err();
+
+        assert_eq!(&*error.str, r#""Title Case""#);
+    }
+
+    #[test]
+    fn test_wire_name() {
+        let field = Field {
+            ident: "is_admin".into(),
+            ty: TypeItem::Basic("Bool".into(), vec![]),
+            json: None,
+            comments: Comments::default(),
+        };
+
+        assert_eq!(field.wire_name(None), "is_admin");
+        assert_eq!(field.wire_name(Some(Case::Camel)), "isAdmin");
+        assert_eq!(field.wire_name(Some(Case::Kebab)), "is-admin");
+    }
+
+    #[test]
+    fn test_parse_json_annotation() {
+        let source = r#"naming "camelCase"
+        type Legacy {
+            @json("user-id") userId: String
+            @json("@type")
+            kind: String, name: String
+        }"#;
+        let mut parser = Parser::new(source);
+        let declarations = parser.parse();
+        let ty = declarations[0].as_type().unwrap();
+
+        assert_eq!(ty.fields[0].json.as_deref(), Some("user-id"));
+        assert_eq!(ty.fields[1].json.as_deref(), Some("@type"));
+        assert_eq!(ty.fields[2].json, None);
+
+        let wire_names = ty
+            .fields
+            .iter()
+            .map(|field| field.wire_name(ty.naming))
+            .collect::<Vec<_>>();
+        assert_eq!(wire_names, vec!["user-id", "@type", "name"]);
+    }
+
+    #[test]
+    fn test_reject_unknown_escape_in_json_annotation() {
+        let source = r#"type Test { @json("a\nb") a: Int }"#;
+        let error = Parser::new(source).parse_schema().unwrap_err();
+
+        assert_eq!(error.to_string(), r"Unexpected '\n' at line 1, column 21");
+    }
+
+    #[test]
+    fn test_reject_unknown_annotation() {
+        let source = r#"type Test { @unknown("value") a: Int }"#;
+        let mut parser = Parser::new(source);
+        let error = parser.parse_declaration().unwrap_err();
+
+        assert_eq!(&*error.str, "unknown");
+    }
+
+    #[test]
+    fn test_parse_alias_and_newtype() {
+        let source = "alias UserId = String
+            newtype Email = String
+            alias Tags = [String]?
+            type User { id: UserId, email: Email }";
+        let mut parser = Parser::new(source);
+        let declarations = parser.parse();
+
+        assert_eq!(
+            declarations[0],
+            Declaration::Alias(Alias {
+                ident: "UserId".into(),
+                ty: TypeItem::Basic("String".into(), vec![]),
+                package: None,
+                comments: Comments::default(),
+            })
+        );
+        assert_eq!(
+            declarations[1],
+            Declaration::Newtype(Newtype {
+                ident: "Email".into(),
+                ty: TypeItem::Basic("String".into(), vec![]),
+                package: None,
+                comments: Comments::default(),
+            })
+        );
+        assert_eq!(declarations[2].ident(), "Tags");
+        assert_eq!(
+            declarations
+                .iter()
+                .map(Declaration::ident)
+                .collect::<Vec<_>>(),
+            vec!["UserId", "Email", "Tags", "User"]
+        );
+        assert!(declarations[3].as_type().is_some());
+    }
+
+    #[test]
+    fn test_reject_alias_without_type() {
+        let source = "alias UserId String";
+        let mut parser = Parser::new(source);
+
+        assert!(parser.parse_alias().is_err());
+    }
+
+    #[test]
+    fn test_parse_imports() {
+        let source = r#"import "account.type"
+            import "../shared/tag.type"
+
+            type User { account: Account, tags: [Tag] }"#;
+        let mut parser = Parser::new(source);
+        let schema = parser.parse_schema().unwrap();
+
+        assert_eq!(
+            schema.imports,
+            vec!["account.type".into(), "../shared/tag.type".into()]
+        );
+        assert_eq!(schema.declarations.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_package() {
+        let source = "package billing.invoices
+            type Invoice { status: Status, shipping: shipping.Status?, items: [Page<core.Item>] }
+            alias Invoices = [Invoice]";
+        let mut parser = Parser::new(source);
+        let declarations = parser.parse();
+
+        assert_eq!(declarations[0].package(), Some("billing.invoices"));
+        assert_eq!(
+            declarations[0].qualified_ident(),
+            "billing.invoices.Invoice"
+        );
+        assert_eq!(declarations[1].package(), Some("billing.invoices"));
+
+        let ty = declarations[0].as_type().unwrap();
+        assert_eq!(ty.fields[0].ty, TypeItem::Basic("Status".into(), vec![]));
+        assert_eq!(ty.fields[1].ty.to_string(), "shipping.Status?");
+        assert_eq!(ty.fields[2].ty.to_string(), "[Page<core.Item>]");
+    }
+
+    #[test]
+    fn test_split_qualified() {
+        assert_eq!(
+            split_qualified("billing.invoices.Invoice"),
+            (Some("billing.invoices"), "Invoice")
+        );
+        assert_eq!(split_qualified("Invoice"), (None, "Invoice"));
+        assert_eq!(qualify(Some("billing"), "Status"), "billing.Status");
+        assert_eq!(qualify(None, "Status"), "Status");
+    }
+
+    #[test]
+    fn test_report_syntax_error() {
+        let source = "type User { name: String }\ntype Account {\n\tid Int\n}";
+        let mut parser = Parser::new(source);
+        let error = parser.parse_schema().unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError {
+                line: 3,
+                column: 5,
+                found: "'Int'".into(),
+            }
+        );
+        assert_eq!(error.to_string(), "Unexpected 'Int' at line 3, column 5");
+
+        // Declarations before the error are still available when parsing leniently
+        assert_eq!(Parser::new(source).parse().len(), 1);
+    }
+
+    #[test]
+    fn test_report_incomplete_declarations() {
+        let error = |source| Parser::new(source).parse_schema().unwrap_err().to_string();
+
+        assert_eq!(
+            error("type Test {\n\tname: String\n"),
+            "Unexpected end of file at line 3, column 1"
+        );
+        assert_eq!(
+            error("type Test { pair: (Int, Int) }"),
+            "Unexpected '(' at line 1, column 19"
+        );
+        assert_eq!(
+            error("alias Test ="),
+            "Unexpected end of file at line 1, column 13"
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_schema() {
+        let schema = Parser::new("").parse_schema().unwrap();
+        assert_eq!(schema, Schema::default());
+    }
 }
@@ -13,26 +13,42 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    /// Returns the next token kind and range in the source without mutating the position directly
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the next token kind and range in the source without mutating the position directly, skipping comments
     fn next_kind(&self) -> (TokenKind, Range<usize>) {
-        if self.position >= self.source.len() {
-            return (TokenKind::EOF, self.position..self.position);
+        let mut position = self.position;
+        loop {
+            match self.kind_at(position) {
+                (TokenKind::Comment, range) => position = range.end,
+                next => return next,
+            }
         }
+    }
 
-        let slice = &self.source[self.position..];
-        let mut start = self.position;
+    /// Returns the kind and range of the token at a position, including comments
+    fn kind_at(&self, position: usize) -> (TokenKind, Range<usize>) {
+        if position >= self.source.len() {
+            return (TokenKind::Eof, position..position);
+        }
+
+        let slice = &self.source[position..];
+        let mut start = position;
 
         for ch in slice.chars() {
             if ch.is_whitespace() && ch != '\n' {
-                start += 1;
+                start += ch.len_utf8();
             } else {
                 break;
             }
         }
 
-        let mut new_position = start + 1;
-
         let slice = &self.source[start..];
+        // Positions always advance by whole characters, so that invalid multi-byte characters do not split the source
+        let mut new_position = start + slice.chars().next().map_or(1, char::len_utf8);
+
         let next = match slice.chars().nth(0) {
             Some('[') => TokenKind::BracketOpen,
             Some(']') => TokenKind::BracketClose,
@@ -40,10 +56,36 @@ impl<'a> Tokenizer<'a> {
             Some('}') => TokenKind::BraceClose,
             Some('(') => TokenKind::ParenOpen,
             Some(')') => TokenKind::ParenClose,
+            Some('<') => TokenKind::AngleOpen,
+            Some('>') => TokenKind::AngleClose,
             Some('?') => TokenKind::QuestionMark,
             Some(',') => TokenKind::Comma,
             Some(':') => TokenKind::Colon,
+            Some('=') => TokenKind::Equals,
+            Some('.') => TokenKind::Dot,
+            Some('@') => TokenKind::At,
             Some('\n') => TokenKind::Newline,
+            Some('/') if slice.starts_with("//") => {
+                new_position = start + slice.find('\n').unwrap_or(slice.len());
+                TokenKind::Comment
+            }
+            Some('"') => {
+                // Find the closing quote, skipping escaped characters
+                let mut escaped = false;
+                let end = slice[1..].find(|c: char| {
+                    let is_end = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    is_end || c == '\n'
+                });
+
+                match end.map(|end| end + 1) {
+                    Some(end) if slice[end..].starts_with('"') => {
+                        new_position = start + end + 1;
+                        TokenKind::StringLiteral
+                    }
+                    _ => TokenKind::Invalid,
+                }
+            }
             Some(c) if c.is_alphabetic() => {
                 let end = slice
                     .find(|c: char| !c.is_alphanumeric() && c != '_')
@@ -60,7 +102,7 @@ impl<'a> Tokenizer<'a> {
             Some(_) => TokenKind::Invalid,
             None => {
                 new_position = start;
-                TokenKind::EOF
+                TokenKind::Eof
             }
         };
 
@@ -89,6 +131,51 @@ impl<'a> Tokenizer<'a> {
         result
     }
 
+    /// Returns the kind of the next token after any newlines, lifting contextual keywords into their keyword form
+    pub fn peek_keyword(&self) -> TokenKind {
+        let mut lookahead = Tokenizer {
+            source: self.source,
+            position: self.position,
+        };
+        lookahead.next_skip_newline().into_keyword().kind
+    }
+
+    /// Takes the comments before the next token, where an empty comment stands for blank lines between them
+    pub fn leading_comments(&mut self) -> Vec<Box<str>> {
+        let mut comments = Vec::new();
+        let mut position = self.position;
+        let mut newlines = 0;
+        loop {
+            match self.kind_at(position) {
+                (TokenKind::Newline, range) => {
+                    newlines += 1;
+                    position = range.end;
+                }
+                (TokenKind::Comment, range) => {
+                    if newlines > 1 && !comments.is_empty() {
+                        comments.push("".into());
+                    }
+                    comments.push(self.source[range.clone()].trim_end().into());
+                    newlines = 0;
+                    position = range.end;
+                    self.position = position;
+                }
+                _ => return comments,
+            }
+        }
+    }
+
+    /// Takes a comment at the end of the current line
+    pub fn trailing_comment(&mut self) -> Option<Box<str>> {
+        match self.kind_at(self.position) {
+            (TokenKind::Comment, range) => {
+                self.position = range.end;
+                Some(self.source[range].trim_end().into())
+            }
+            _ => None,
+        }
+    }
+
     pub fn next_skip_newline(&mut self) -> Token {
         while self.peek() == TokenKind::Newline {
             self.next();
@@ -121,7 +208,7 @@ impl<'a> Tokenizer<'a> {
         loop {
             let (token, range) = self.next_kind();
             self.position = range.end;
-            if token == TokenKind::EOF {
+            if token == TokenKind::Eof {
                 break;
             }
             tokens.push((token, range))
@@ -141,9 +228,17 @@ impl<'a> Tokenizer<'a> {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Span(RangeInclusive<usize>);
 
+impl Span {
+    /// The byte offset of the start of the span in the source
+    pub fn start(&self) -> usize {
+        *self.0.start()
+    }
+}
+
 impl From<Range<usize>> for Span {
     fn from(value: Range<usize>) -> Self {
-        Span(value.start..=(value.end - 1))
+        // An empty range at the start of the source, e.g. the end of an empty file, must not underflow
+        Span(value.start..=value.end.saturating_sub(1))
     }
 }
 
@@ -172,13 +267,23 @@ impl Token {
     /// Returns the keyword token, if the receiver would be a valid keyword, otherwise returns the receiver unchanged
     /// This is useful to lift contextual keywords into their keyword form
     pub fn into_keyword(self) -> Token {
-        match self.kind {
-            TokenKind::Ident if self.str.trim() == "type" => Token {
-                span: self.span,
-                kind: TokenKind::TypeKeyword,
-                str: self.str,
+        let kind = match self.kind {
+            TokenKind::Ident => match self.str.trim() {
+                "type" => TokenKind::TypeKeyword,
+                "naming" => TokenKind::NamingKeyword,
+                "alias" => TokenKind::AliasKeyword,
+                "newtype" => TokenKind::NewtypeKeyword,
+                "import" => TokenKind::ImportKeyword,
+                "package" => TokenKind::PackageKeyword,
+                _ => return self,
             },
-            _ => self,
+            _ => return self,
+        };
+
+        Token {
+            span: self.span,
+            kind,
+            str: self.str,
         }
     }
 }
@@ -191,20 +296,66 @@ pub enum TokenKind {
     BracketClose,
     ParenOpen,
     ParenClose,
+    AngleOpen,
+    AngleClose,
 
     Colon,
+    Equals,
+    Dot,
     QuestionMark,
+    At,
 
     Comma,
     Newline,
+    /// A comment from `//` up to the end of the line
+    Comment,
 
     TypeIdent,
     Ident,
+    StringLiteral,
 
     TypeKeyword,
+    NamingKeyword,
+    AliasKeyword,
+    NewtypeKeyword,
+    ImportKeyword,
+    PackageKeyword,
 
     Invalid,
-    EOF,
+    Eof,
+}
+
+impl Token {
+    /// Returns the content of a string literal token without quotes and with escape sequences resolved
+    ///
+    /// Only `\"` and `\\` are escape sequences, any other backslash is returned as an invalid token.
+    pub fn unquote(&self) -> Result<String, Token> {
+        let (offset, inner) = match self.str.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(inner) => (self.span.start() + 1, inner),
+            None => (self.span.start(), &*self.str),
+        };
+
+        let mut unquoted = String::with_capacity(inner.len());
+        let mut chars = inner.char_indices();
+        while let Some((index, ch)) = chars.next() {
+            if ch != '\\' {
+                unquoted.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => unquoted.push(escaped),
+                escaped => {
+                    let end = escaped.map_or(inner.len(), |(index, ch)| index + ch.len_utf8());
+                    return Err(
+                        TokenKind::Invalid.at(offset + index..offset + end, &inner[index..end])
+                    );
+                }
+            }
+        }
+
+        Ok(unquoted)
+    }
 }
 
 impl TokenKind {
@@ -215,9 +366,22 @@ impl TokenKind {
             str: str.into(),
         }
     }
+}
 
-    pub fn is_delim(self) -> bool {
-        self == TokenKind::Comma || self == TokenKind::Newline
+/// All comments of a source in the order they are written
+pub fn comments(source: &str) -> Vec<Token> {
+    let tokenizer = Tokenizer::new(source);
+    let mut comments = Vec::new();
+    let mut position = 0;
+    loop {
+        match tokenizer.kind_at(position) {
+            (TokenKind::Eof, _) => return comments,
+            (TokenKind::Comment, range) => {
+                position = range.end;
+                comments.push(TokenKind::Comment.at(range.clone(), source[range].trim_end()));
+            }
+            (_, range) => position = range.end,
+        }
     }
 }
 
@@ -324,6 +488,175 @@ mod tests {
         assert_ne!(token.into_keyword().kind, TokenKind::Ident);
     }
 
+    #[test]
+    fn test_tokenize_generic() {
+        let source = "Page<T>";
+
+        let mut lexer = Tokenizer::new(source);
+        let tokens: Vec<_> = lexer.collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::TypeIdent.at(0..4, "Page"),
+                TokenKind::AngleOpen.at(4, "<"),
+                TokenKind::TypeIdent.at(5, "T"),
+                TokenKind::AngleClose.at(6, ">"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_annotation() {
+        let source = r#"@json("user-id")"#;
+
+        let mut lexer = Tokenizer::new(source);
+        let tokens: Vec<_> = lexer.collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::At.at(0, "@"),
+                TokenKind::Ident.at(1..5, "json"),
+                TokenKind::ParenOpen.at(5, "("),
+                TokenKind::StringLiteral.at(6..15, r#""user-id""#),
+                TokenKind::ParenClose.at(15, ")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unquote_string_literal() {
+        let source = r#""say \"hi\"\\" rest"#;
+
+        let mut lexer = Tokenizer::new(source);
+        let token = lexer.next();
+
+        assert_eq!(token.kind, TokenKind::StringLiteral);
+        assert_eq!(token.unquote().unwrap(), r#"say "hi"\"#);
+    }
+
+    #[test]
+    fn test_reject_unknown_escape_sequence() {
+        let source = r#"  "a\nb""#;
+
+        let mut lexer = Tokenizer::new(source);
+        let error = lexer.next().unquote().unwrap_err();
+
+        assert_eq!(error.kind, TokenKind::Invalid);
+        assert_eq!(&*error.str, r"\n");
+        assert_eq!(error.span.start(), 4);
+    }
+
+    #[test]
+    fn test_unterminated_string_literal() {
+        let source = "\"open\nnext";
+
+        let mut lexer = Tokenizer::new(source);
+
+        assert_eq!(lexer.next().kind, TokenKind::Invalid);
+    }
+
+    #[test]
+    fn test_convert_naming_keyword() {
+        let token = TokenKind::Ident.at(0, "naming");
+        assert_eq!(token.into_keyword().kind, TokenKind::NamingKeyword);
+    }
+
+    #[test]
+    fn test_tokenize_alias() {
+        let source = "alias UserId = String";
+
+        let mut lexer = Tokenizer::new(source);
+
+        assert_eq!(lexer.next().into_keyword().kind, TokenKind::AliasKeyword);
+        assert_eq!(lexer.next().kind, TokenKind::TypeIdent);
+        assert_eq!(lexer.next().kind, TokenKind::Equals);
+        assert_eq!(lexer.next().kind, TokenKind::TypeIdent);
+    }
+
+    #[test]
+    fn test_convert_newtype_keyword() {
+        let token = TokenKind::Ident.at(0, "newtype");
+        assert_eq!(token.into_keyword().kind, TokenKind::NewtypeKeyword);
+    }
+
+    #[test]
+    fn test_convert_import_keyword() {
+        let token = TokenKind::Ident.at(0, "import");
+        assert_eq!(token.into_keyword().kind, TokenKind::ImportKeyword);
+    }
+
+    #[test]
+    fn test_tokenize_qualified_ident() {
+        let source = "billing.Status";
+
+        let mut lexer = Tokenizer::new(source);
+
+        assert_eq!(lexer.next().kind, TokenKind::Ident);
+        assert_eq!(lexer.next().kind, TokenKind::Dot);
+        assert_eq!(lexer.next().kind, TokenKind::TypeIdent);
+        assert_eq!(lexer.next().kind, TokenKind::Eof);
+    }
+
+    #[test]
+    fn test_tokenize_empty_source() {
+        let mut lexer = Tokenizer::new("");
+        let token = lexer.next();
+
+        assert_eq!(token.kind, TokenKind::Eof);
+        assert_eq!(token.span.start(), 0);
+    }
+
+    #[test]
+    fn test_skip_comments() {
+        let source = "a // first: Int\n// second\nb /c";
+
+        let mut lexer = Tokenizer::new(source);
+        let tokens: Vec<_> = lexer.collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::Ident.at(0, "a"),
+                TokenKind::Newline.at(15, "\n"),
+                TokenKind::Newline.at(25, "\n"),
+                TokenKind::Ident.at(26, "b"),
+                TokenKind::Invalid.at(28, "/"),
+                TokenKind::Ident.at(29, "c"),
+            ]
+        );
+        assert_eq!(
+            comments(source),
+            vec![
+                TokenKind::Comment.at(2..15, "// first: Int"),
+                TokenKind::Comment.at(16..25, "// second")
+            ]
+        );
+        assert_eq!(comments(r#"@json("http://example.com")"#), vec![]);
+    }
+
+    #[test]
+    fn test_take_comments() {
+        let source = "a // trailing\n\n// first\n\n\n// second  \n// third\nb";
+
+        let mut lexer = Tokenizer::new(source);
+        lexer.next();
+
+        assert_eq!(lexer.trailing_comment().as_deref(), Some("// trailing"));
+        assert_eq!(lexer.trailing_comment(), None);
+        assert_eq!(
+            lexer.leading_comments(),
+            vec![
+                "// first".into(),
+                "".into(),
+                "// second".into(),
+                "// third".into()
+            ]
+        );
+        assert_eq!(lexer.next_skip_newline().str.as_ref(), "b");
+    }
+
     #[test]
     fn test_convert_type_keyword_no_match() {
         let token = TokenKind::Ident.at(0, "noKeyword");
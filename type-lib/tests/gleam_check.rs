@@ -0,0 +1,81 @@
+//! Type-checks the Gleam code that is generated for the fixture schema with the Gleam compiler.
+//!
+//! The checks need a `gleam` executable on the `PATH` and fetch the Gleam dependencies, so they are ignored by default.
+//! Run them with `cargo test --test gleam_check -- --ignored`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use type_lib::{
+    generator::{
+        gleam::{DecodeVersion, GleamTypeGenerator},
+        Generator,
+    },
+    loader::Loader,
+};
+
+fn fixture(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+/// Writes the generated modules into a copy of the fixture project and runs `gleam check` on it
+fn check_generated(name: &str, decode_version: DecodeVersion) {
+    let version = Command::new("gleam").arg("--version").output();
+    assert!(
+        version.is_ok_and(|output| output.status.success()),
+        "`gleam` is not installed, see https://gleam.run/getting-started/installing"
+    );
+
+    let mut generator = GleamTypeGenerator::default().with_decode_version(decode_version);
+    let symbols = Loader::new().load(fixture("schema.type")).unwrap();
+    for declaration in symbols.declarations() {
+        generator.add_declaration(declaration);
+    }
+
+    let project = env::temp_dir().join(format!("type-lang-gleam-{name}-{}", std::process::id()));
+    let src = project.join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::copy(
+        fixture(&format!("gleam/{name}/gleam.toml")),
+        project.join("gleam.toml"),
+    )
+    .unwrap();
+
+    let ext = generator.file_extension();
+    for file in generator.generate() {
+        // File names are module paths, so packages are written to their own folders
+        let path = src.join(format!("{}.{ext}", file.name));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, file.content).unwrap();
+    }
+
+    let output = Command::new("gleam")
+        .arg("check")
+        .current_dir(&project)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&project).unwrap();
+
+    assert!(
+        output.status.success(),
+        "generated Gleam code does not type-check:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+#[ignore = "needs the Gleam compiler"]
+fn generated_legacy_gleam_type_checks() {
+    check_generated("legacy", DecodeVersion::Legacy);
+}
+
+#[test]
+#[ignore = "needs the Gleam compiler"]
+fn generated_stdlib_gleam_type_checks() {
+    check_generated("stdlib", DecodeVersion::Stdlib);
+}
@@ -1,3 +1,6 @@
+// Generated by type-lang from User.type, do not edit.
+// schema-hash: 5b577e118aa68a81
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct User {
 	pub name: String,